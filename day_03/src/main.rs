@@ -1,6 +1,7 @@
 use std::{
     collections::{hash_map::RandomState, HashSet},
     error::Error,
+    fmt,
 };
 
 use itertools::Itertools;
@@ -13,46 +14,184 @@ fn priority(c: char) -> u32 {
     }
 }
 
+fn sorted_chars(chars: HashSet<char, RandomState>) -> Vec<char> {
+    let mut chars: Vec<char> = chars.into_iter().collect();
+    chars.sort_unstable();
+    chars
+}
+
+// Characters that `priority` would silently mis-score (or underflow on)
+fn non_letter_chars(line: &str) -> Vec<char> {
+    sorted_chars(line.chars().filter(|c| !c.is_ascii_alphabetic()).collect())
+}
+
+// Splits on the middle char rather than the middle byte so non-ASCII lines don't panic
+fn split_compartments(line: &str) -> (&str, &str) {
+    let middle = line
+        .char_indices()
+        .nth(line.chars().count() / 2)
+        .map_or(line.len(), |(index, _)| index);
+    line.split_at(middle)
+}
+
+enum Diagnostic {
+    NonLetters {
+        line: usize,
+        chars: Vec<char>,
+    },
+    Rucksack {
+        line: usize,
+        common_chars: Vec<char>,
+    },
+    Group {
+        lines: (usize, usize, usize),
+        common_chars: Vec<char>,
+    },
+    IncompleteGroup {
+        lines: Vec<usize>,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::NonLetters { line, chars } => {
+                write!(f, "line {line}: non-letter characters {chars:?}")
+            }
+            Diagnostic::Rucksack { line, common_chars } => write!(
+                f,
+                "line {line}: expected exactly one item in both compartments, got {common_chars:?}"
+            ),
+            Diagnostic::Group {
+                lines: (first, second, third),
+                common_chars,
+            } => write!(
+                f,
+                "lines {first}-{third}: expected exactly one item common to the group \
+                 ({first}, {second}, {third}), got {common_chars:?}"
+            ),
+            Diagnostic::IncompleteGroup { lines } => {
+                write!(
+                    f,
+                    "lines {lines:?}: trailing group has fewer than 3 rucksacks"
+                )
+            }
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
+    // In lenient mode, anomalous rucksacks are reported and skipped instead of panicking
+    let lenient = std::env::args().skip(1).any(|arg| arg == "--lenient");
+
     let input_file = std::fs::read_to_string("input.txt")?;
-    let input_lines: Vec<&str> = input_file.trim().lines().collect();
+    // Only trim the end so that line numbers in diagnostics stay accurate
+    let input_lines: Vec<&str> = input_file.trim_end().lines().collect();
+
+    let mut diagnostics = Vec::new();
 
     let mut part_1_total = 0;
-    for line in input_lines.iter() {
-        let parts = line.split_at(line.len() / 2);
+    for (line_index, line) in input_lines.iter().enumerate() {
+        let line_number = line_index + 1;
+
+        if lenient {
+            let non_letters = non_letter_chars(line);
+            if !non_letters.is_empty() {
+                diagnostics.push(Diagnostic::NonLetters {
+                    line: line_number,
+                    chars: non_letters,
+                });
+            }
+        }
+
+        let parts = split_compartments(line);
 
         let part_1_chars: HashSet<char, RandomState> = HashSet::from_iter(parts.0.chars());
         let part_2_chars: HashSet<char, RandomState> = HashSet::from_iter(parts.1.chars());
 
         let mut common_chars = &part_1_chars & &part_2_chars;
         if common_chars.len() != 1 {
+            if lenient {
+                diagnostics.push(Diagnostic::Rucksack {
+                    line: line_number,
+                    common_chars: sorted_chars(common_chars),
+                });
+                continue;
+            }
             panic!("Expected exactly one common char, got {:?}", common_chars);
         }
 
         let common_char = common_chars.drain().next().unwrap();
 
+        if lenient && !common_char.is_ascii_alphabetic() {
+            // Already reported as a non-letter, don't let it skew the total
+            continue;
+        }
+
         part_1_total += priority(common_char);
     }
 
     println!("Part 1: {part_1_total}");
 
     let mut part_2_total = 0;
-    for (rucksack_1, rucksack_2, rucksack_3) in input_lines.iter().tuples() {
+    let mut groups = input_lines.iter().enumerate().tuples();
+    for ((index_1, rucksack_1), (index_2, rucksack_2), (index_3, rucksack_3)) in groups.by_ref() {
         let rucksack_1_chars: HashSet<char, RandomState> = HashSet::from_iter(rucksack_1.chars());
         let rucksack_2_chars: HashSet<char, RandomState> = HashSet::from_iter(rucksack_2.chars());
         let rucksack_3_chars: HashSet<char, RandomState> = HashSet::from_iter(rucksack_3.chars());
 
         let mut common_chars = &((&rucksack_1_chars) & (&rucksack_2_chars)) & (&rucksack_3_chars);
         if common_chars.len() != 1 {
+            if lenient {
+                diagnostics.push(Diagnostic::Group {
+                    lines: (index_1 + 1, index_2 + 1, index_3 + 1),
+                    common_chars: sorted_chars(common_chars),
+                });
+                continue;
+            }
             panic!("Expected exactly one common char, got {:?}", common_chars);
         };
 
         let common_char = common_chars.drain().next().unwrap();
 
+        if lenient && !common_char.is_ascii_alphabetic() {
+            continue;
+        }
+
         part_2_total += priority(common_char);
     }
 
+    let leftover_lines: Vec<usize> = groups.into_buffer().map(|(index, _)| index + 1).collect();
+    if lenient && !leftover_lines.is_empty() {
+        diagnostics.push(Diagnostic::IncompleteGroup {
+            lines: leftover_lines,
+        });
+    }
+
     println!("Part 2: {part_2_total}");
 
+    if !diagnostics.is_empty() {
+        for diagnostic in &diagnostics {
+            eprintln!("{diagnostic}");
+        }
+
+        let count = |predicate: fn(&Diagnostic) -> bool| {
+            diagnostics
+                .iter()
+                .filter(|&diagnostic| predicate(diagnostic))
+                .count()
+        };
+        eprintln!(
+            "{} problem(s): {} line(s) with non-letters, {} rucksack(s) and {} group(s) without exactly one common item, and {} incomplete trailing group(s)",
+            diagnostics.len(),
+            count(|diagnostic| matches!(diagnostic, Diagnostic::NonLetters { .. })),
+            count(|diagnostic| matches!(diagnostic, Diagnostic::Rucksack { .. })),
+            count(|diagnostic| matches!(diagnostic, Diagnostic::Group { .. })),
+            count(|diagnostic| matches!(diagnostic, Diagnostic::IncompleteGroup { .. })),
+        );
+
+        std::process::exit(1);
+    }
+
     Ok(())
 }
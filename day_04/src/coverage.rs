@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use crate::section_range::SectionRange;
+
+// Camp-wide view of how many assignments cover each section, built with a sweep line over all
+// assignment boundaries. Segments are maximal runs of sections sharing the same count, sorted and
+// contiguous from the first assigned section to the last one.
+pub struct Coverage {
+    assignments: Vec<SectionRange>,
+    segments: Vec<(SectionRange, usize)>,
+}
+
+impl Coverage {
+    pub fn new(assignments: &[SectionRange]) -> Self {
+        // +1 when entering a range, -1 right after leaving it. u64 so `end + 1` can't overflow.
+        let mut events: Vec<(u64, isize)> = assignments
+            .iter()
+            .flat_map(|range| [(range.start() as u64, 1), (range.end() as u64 + 1, -1)])
+            .collect();
+        events.sort_unstable();
+
+        let mut segments: Vec<(SectionRange, usize)> = Vec::new();
+        let mut count = 0isize;
+        let mut previous_position = None;
+
+        for (position, delta) in events {
+            if let Some(previous_position) = previous_position {
+                if position > previous_position {
+                    let segment_count = count as usize;
+                    let segment_end = (position - 1) as u32;
+
+                    match segments.last_mut() {
+                        // Extend the previous segment when the count didn't actually change
+                        Some((last, last_count)) if *last_count == segment_count => {
+                            *last = SectionRange::new(last.start(), segment_end).unwrap();
+                        }
+                        _ => segments.push((
+                            SectionRange::new(previous_position as u32, segment_end).unwrap(),
+                            segment_count,
+                        )),
+                    }
+                }
+            }
+
+            count += delta;
+            previous_position = Some(position);
+        }
+
+        Self {
+            assignments: assignments.to_vec(),
+            segments,
+        }
+    }
+
+    pub fn segments(&self) -> &[(SectionRange, usize)] {
+        &self.segments
+    }
+
+    // From the lowest assigned section to the highest one
+    pub fn span(&self) -> Option<SectionRange> {
+        let first = self.segments.first()?.0;
+        let last = self.segments.last()?.0;
+        Some(SectionRange::new(first.start(), last.end()).unwrap())
+    }
+
+    // Gaps within the span that nobody is assigned to
+    pub fn uncovered(&self) -> Vec<SectionRange> {
+        self.segments
+            .iter()
+            .filter(|(_, count)| *count == 0)
+            .map(|(range, _)| *range)
+            .collect()
+    }
+
+    // Highest number of elves assigned to a single section, and every range where it's reached
+    pub fn max_overlap(&self) -> (usize, Vec<SectionRange>) {
+        let max = self
+            .segments
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+
+        let ranges = self
+            .segments
+            .iter()
+            .filter(|(_, count)| max > 0 && *count == max)
+            .map(|(range, _)| *range)
+            .collect();
+
+        (max, ranges)
+    }
+
+    // Number of sections covered by exactly `k` elves, for every `k` that occurs within the span
+    pub fn histogram(&self) -> BTreeMap<usize, u64> {
+        let mut histogram = BTreeMap::new();
+        for (range, count) in &self.segments {
+            *histogram.entry(*count).or_insert(0) += range.section_count();
+        }
+        histogram
+    }
+
+    // Indices of assignments whose sections are all covered by the union of the other assignments.
+    // An assignment always covers itself, so it's redundant iff it touches no segment with a count
+    // of exactly 1.
+    pub fn redundant(&self) -> Vec<usize> {
+        let singly_covered: Vec<SectionRange> = self
+            .segments
+            .iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(range, _)| *range)
+            .collect();
+
+        self.assignments
+            .iter()
+            .enumerate()
+            .filter(|(_, assignment)| {
+                // Singly-covered segments are disjoint and sorted, so the only candidate is the
+                // first one that ends at or after the assignment's start
+                let candidate =
+                    singly_covered.partition_point(|range| range.end() < assignment.start());
+                singly_covered
+                    .get(candidate)
+                    .is_none_or(|range| !range.overlaps(assignment))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::section_range::SectionRange;
+
+pub type Entry<T> = (SectionRange, T);
+
+// Static augmented interval tree. Entries are sorted by range and laid out as an implicit balanced
+// BST: the root of the subtree over `entries[low..high]` is at `(low + high) / 2`, and `max_end`
+// holds the highest range end within that subtree so whole subtrees can be pruned.
+pub struct IntervalTree<T> {
+    entries: Vec<Entry<T>>,
+    max_end: Vec<u32>,
+}
+
+impl<T> IntervalTree<T> {
+    pub fn new(mut entries: Vec<Entry<T>>) -> Self {
+        entries.sort_by_key(|(range, _)| *range);
+
+        let mut max_end = vec![0; entries.len()];
+        Self::build_max_end(&entries, &mut max_end, 0, entries.len());
+
+        Self { entries, max_end }
+    }
+
+    fn build_max_end(entries: &[Entry<T>], max_end: &mut [u32], low: usize, high: usize) -> u32 {
+        if low >= high {
+            return 0;
+        }
+
+        let middle = (low + high) / 2;
+        let left = Self::build_max_end(entries, max_end, low, middle);
+        let right = Self::build_max_end(entries, max_end, middle + 1, high);
+        max_end[middle] = entries[middle].0.end().max(left).max(right);
+        max_end[middle]
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry<T>> {
+        self.entries.iter()
+    }
+
+    // Entries whose range contains `section`, ordered by range
+    pub fn stab(&self, section: u32) -> Vec<&Entry<T>> {
+        self.overlapping(&SectionRange::new(section, section).unwrap())
+    }
+
+    // Entries whose range shares at least one section with `query`, ordered by range
+    pub fn overlapping(&self, query: &SectionRange) -> Vec<&Entry<T>> {
+        let mut indices = Vec::new();
+        self.collect_overlapping(query, 0, self.entries.len(), &mut indices);
+        indices
+            .into_iter()
+            .map(|index| &self.entries[index])
+            .collect()
+    }
+
+    // Pushes the indices of overlapping entries in the subtree over `entries[low..high]`, in order
+    fn collect_overlapping(
+        &self,
+        query: &SectionRange,
+        low: usize,
+        high: usize,
+        indices: &mut Vec<usize>,
+    ) {
+        if low >= high {
+            return;
+        }
+
+        let middle = (low + high) / 2;
+
+        // Nothing in this subtree reaches the query
+        if self.max_end[middle] < query.start() {
+            return;
+        }
+
+        self.collect_overlapping(query, low, middle, indices);
+
+        // Everything from here on starts after the query ends
+        if self.entries[middle].0.start() > query.end() {
+            return;
+        }
+
+        if self.entries[middle].0.overlaps(query) {
+            indices.push(middle);
+        }
+
+        self.collect_overlapping(query, middle + 1, high, indices);
+    }
+
+    // Every unordered pair of overlapping entries, in O(n log n + pairs)
+    pub fn overlapping_pairs(&self) -> Vec<(&Entry<T>, &Entry<T>)> {
+        let mut pairs = Vec::new();
+        let mut indices = Vec::new();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            indices.clear();
+            self.collect_overlapping(&entry.0, 0, self.entries.len(), &mut indices);
+
+            // Each pair is found from both sides; keep it once, from its first entry in sorted order
+            pairs.extend(
+                indices
+                    .iter()
+                    .filter(|&&other_index| other_index > index)
+                    .map(|&other_index| (entry, &self.entries[other_index])),
+            );
+        }
+
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks every query against a scan of the entries, sorted the same way as in the tree
+    fn assert_matches_scan(entries: Vec<Entry<usize>>, queries: &[SectionRange]) {
+        let mut sorted = entries.clone();
+        sorted.sort_by_key(|(range, _)| *range);
+        let tree = IntervalTree::new(entries);
+
+        for query in queries {
+            let expected: Vec<_> = sorted
+                .iter()
+                .filter(|(range, _)| range.overlaps(query))
+                .collect();
+            assert_eq!(tree.overlapping(query), expected, "overlapping {query}");
+
+            for section in [query.start(), query.end()] {
+                let expected: Vec<_> = sorted
+                    .iter()
+                    .filter(|(range, _)| range.contains_section(section))
+                    .collect();
+                assert_eq!(tree.stab(section), expected, "stab {section}");
+            }
+        }
+
+        let mut expected = Vec::new();
+        for (index, entry) in sorted.iter().enumerate() {
+            for other in &sorted[index + 1..] {
+                if entry.0.overlaps(&other.0) {
+                    expected.push((entry, other));
+                }
+            }
+        }
+        assert_eq!(tree.overlapping_pairs(), expected);
+    }
+
+    #[test]
+    fn matches_scan_on_random_ranges() {
+        let mut state = 4u64;
+        let mut next = move |bound: u32| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % u64::from(bound)) as u32
+        };
+
+        for round in 0..500 {
+            // Small sections overlap often; every other round sits at the top of the u32 range
+            let offset = if round % 2 == 0 { 0 } else { u32::MAX - 60 };
+            let entry_count = next(40) as usize;
+            let mut random_range = || {
+                let start = next(60);
+                SectionRange::new(offset + start, offset + start + next(61 - start)).unwrap()
+            };
+
+            let entries: Vec<_> = (0..entry_count).map(|id| (random_range(), id)).collect();
+            let queries: Vec<_> = (0..20).map(|_| random_range()).collect();
+            assert_matches_scan(entries, &queries);
+        }
+    }
+}
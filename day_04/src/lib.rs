@@ -0,0 +1,3 @@
+pub mod coverage;
+pub mod interval_tree;
+pub mod section_range;
@@ -1,56 +1,216 @@
-use std::error::Error;
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+};
 
-macro_rules! tuple_map {
-    ($f:expr => $($x:expr),*) => {
-        ($( $f($x) ),*)
+use day_04::{
+    coverage::Coverage,
+    interval_tree::IntervalTree,
+    section_range::{parse_pair, SectionRange},
+};
+
+// Identifies an elf by its position in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Elf {
+    line: usize,
+    position: usize, // 1 or 2 within the line
+}
+
+impl fmt::Display for Elf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} elf {}", self.line, self.position)
+    }
+}
+
+enum Query {
+    Covers(u32),
+    Overlaps(SectionRange),
+    Pairs,
+}
+
+impl Query {
+    fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            ["covers", section] => Ok(Query::Covers(section.parse()?)),
+            ["overlaps", range] => Ok(Query::Overlaps(range.parse()?)),
+            ["pairs"] => Ok(Query::Pairs),
+            _ => Err(format!(
+                "unknown query {s:?}, expected `covers <section>`, `overlaps <a>-<b>` or `pairs`"
+            )
+            .into()),
+        }
+    }
+}
+
+fn run_query(query: &Query, tree: &IntervalTree<Elf>) {
+    match query {
+        Query::Covers(section) => {
+            let elves = tree.stab(*section);
+            println!("{} elves cover section {section}", elves.len());
+            for (range, elf) in elves {
+                println!("  {elf}: {range}");
+            }
+        }
+        Query::Overlaps(query_range) => {
+            let elves = tree.overlapping(query_range);
+            println!("{} assignments overlap {query_range}", elves.len());
+            for (range, elf) in elves {
+                println!("  {elf}: {range}");
+            }
+        }
+        Query::Pairs => {
+            // Pairs within a line are what part 2 already counts
+            let pairs: Vec<_> = tree
+                .overlapping_pairs()
+                .into_iter()
+                .filter(|((_, elf_1), (_, elf_2))| elf_1.line != elf_2.line)
+                .collect();
+            println!("{} overlapping pairs across different lines", pairs.len());
+            for ((range_1, elf_1), (range_2, elf_2)) in pairs {
+                println!("  {elf_1} ({range_1}) and {elf_2} ({range_2})");
+            }
+        }
+    }
+}
+
+// Runs one query per line, skipping blank lines and `#` comments. Bad queries are reported and skipped.
+fn run_queries(
+    queries: impl BufRead,
+    tree: &IntervalTree<Elf>,
+    prompt: bool,
+) -> Result<(), Box<dyn Error>> {
+    let show_prompt = || -> io::Result<()> {
+        if prompt {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        Ok(())
     };
+
+    show_prompt()?;
+    for (line_index, line) in queries.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if !line.is_empty() && !line.starts_with('#') {
+            match Query::parse(line) {
+                Ok(query) => run_query(&query, tree),
+                Err(error) => eprintln!("query {}: {error}", line_index + 1),
+            }
+        }
+
+        show_prompt()?;
+    }
+
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let show_coverage = args.iter().any(|arg| arg == "--coverage");
+    let interactive = args.iter().any(|arg| arg == "--interactive");
+    let query_file = match args.iter().position(|arg| arg == "--queries") {
+        Some(index) => Some(args.get(index + 1).ok_or("--queries expects a file path")?),
+        None => None,
+    };
+
     let input = include_str!("../input.txt");
     let lines = input.lines();
 
+    let pairs = lines
+        .enumerate()
+        .map(|(line_index, line)| {
+            parse_pair(line).map_err(|error| format!("line {}: {error}", line_index + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     let mut part_1_total = 0;
 
     let mut part_2_total = 0;
 
-    for line in lines {
-        let (range_1, range_2) = line.split_once(',').unwrap();
+    for (range_1, range_2) in &pairs {
+        if range_1.contains(range_2) || range_2.contains(range_1) {
+            part_1_total += 1;
+        }
+
+        if range_1.overlaps(range_2) {
+            part_2_total += 1;
+        }
+    }
+
+    println!("Part 1: {part_1_total}");
+    println!("Part 2: {part_2_total}");
 
-        let (range_1, range_2) =
-            tuple_map!(|x: &'static str| x.split_once('-').unwrap() => range_1, range_2);
+    let (assignments, elves): (Vec<SectionRange>, Vec<Elf>) = pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(line_index, (range_1, range_2))| {
+            [(range_1, 1), (range_2, 2)].map(|(range, position)| {
+                let elf = Elf {
+                    line: line_index + 1,
+                    position,
+                };
+                (*range, elf)
+            })
+        })
+        .unzip();
 
-        let (range_1, range_2) = tuple_map!(
-            |x: (&'static str, &'static str)| tuple_map!(
-                |y: &'static str| y.parse::<u32>().unwrap() =>
-                x.0,
-                x.1
-            ) =>
-            range_1,
-            range_2
-        );
+    if show_coverage {
+        let coverage = Coverage::new(&assignments);
+
+        let Some(span) = coverage.span() else {
+            println!("No assignments");
+            return Ok(());
+        };
+        println!("Assigned sections: {span}");
+
+        let uncovered = coverage.uncovered();
+        println!("Sections covered by nobody: {}", format_ranges(&uncovered));
 
-        let (range_1, range_2) = tuple_map!(
-            |(min, max): (u32, u32)| (min..=max) =>
-            range_1,
-            range_2
+        let (max_overlap, max_overlap_ranges) = coverage.max_overlap();
+        println!(
+            "Maximum elves on one section: {max_overlap} (at {})",
+            format_ranges(&max_overlap_ranges)
         );
 
-        if range_1.contains(range_2.start()) && range_1.contains(range_2.end())
-            || range_2.contains(range_1.start()) && range_2.contains(range_1.end())
-        {
-            part_1_total += 1;
+        let redundant = coverage.redundant();
+        println!("Redundant assignments: {}", redundant.len());
+        for index in redundant {
+            println!("  {}: {}", elves[index], assignments[index]);
         }
 
-        if range_1.contains(range_2.start()) || range_1.contains(range_2.end())
-            || range_2.contains(range_1.start()) || range_2.contains(range_1.end())
-        {
-            part_2_total += 1;
+        println!("Coverage histogram (elves: sections):");
+        for (elf_count, sections) in coverage.histogram() {
+            println!("  {elf_count:>3}: {sections}");
         }
     }
 
-    println!("Part 1: {part_1_total}");
-    println!("Part 2: {part_2_total}");
+    if query_file.is_some() || interactive {
+        let tree = IntervalTree::new(assignments.into_iter().zip(elves).collect());
+
+        if let Some(query_file) = query_file {
+            let file = std::fs::File::open(query_file)
+                .map_err(|error| format!("could not open {query_file}: {error}"))?;
+            run_queries(io::BufReader::new(file), &tree, false)?;
+        }
+
+        if interactive {
+            run_queries(io::stdin().lock(), &tree, true)?;
+        }
+    }
 
     Ok(())
 }
+
+fn format_ranges(ranges: &[SectionRange]) -> String {
+    if ranges.is_empty() {
+        return String::from("none");
+    }
+
+    ranges
+        .iter()
+        .map(|range| range.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
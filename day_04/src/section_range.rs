@@ -0,0 +1,180 @@
+use std::{error::Error, fmt, num::ParseIntError, ops::RangeInclusive, str::FromStr};
+
+// An inclusive range of section IDs, e.g. `2-4` covers sections 2, 3 and 4.
+// Ordered by start, then by end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectionRange {
+    start: u32,
+    end: u32,
+}
+
+impl SectionRange {
+    pub fn new(start: u32, end: u32) -> Result<Self, ParseSectionRangeError> {
+        if start > end {
+            return Err(ParseSectionRangeError::Reversed { start, end });
+        }
+
+        Ok(Self { start, end })
+    }
+
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    // Number of sections in the range (never 0). `0-4294967295` has one more than fits in a u32.
+    pub fn section_count(&self) -> u64 {
+        u64::from(self.end - self.start) + 1
+    }
+
+    pub fn contains_section(&self, section: u32) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    // Whether `other` lies entirely within `self`
+    pub fn contains(&self, other: &SectionRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &SectionRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &SectionRange) -> Option<SectionRange> {
+        self.overlaps(other).then(|| SectionRange {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        })
+    }
+
+    // Only defined when the result is a single range, i.e. the ranges overlap or are adjacent
+    pub fn union(&self, other: &SectionRange) -> Option<SectionRange> {
+        let (first, second) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        (second.start <= first.end.saturating_add(1)).then(|| SectionRange {
+            start: first.start,
+            end: first.end.max(second.end),
+        })
+    }
+
+    // Sections of `self` not covered by `other`: zero, one or two ranges, in order
+    pub fn difference(&self, other: &SectionRange) -> Vec<SectionRange> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+
+        let mut remainder = Vec::with_capacity(2);
+        if self.start < other.start {
+            remainder.push(SectionRange {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+        if other.end < self.end {
+            remainder.push(SectionRange {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+        remainder
+    }
+}
+
+impl fmt::Display for SectionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl From<SectionRange> for RangeInclusive<u32> {
+    fn from(range: SectionRange) -> Self {
+        range.start..=range.end
+    }
+}
+
+impl TryFrom<RangeInclusive<u32>> for SectionRange {
+    type Error = ParseSectionRangeError;
+
+    fn try_from(range: RangeInclusive<u32>) -> Result<Self, Self::Error> {
+        SectionRange::new(*range.start(), *range.end())
+    }
+}
+
+impl FromStr for SectionRange {
+    type Err = ParseSectionRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| ParseSectionRangeError::MissingDash(s.to_string()))?;
+
+        let parse_bound = |bound: &str| {
+            bound
+                .parse::<u32>()
+                .map_err(|source| ParseSectionRangeError::InvalidBound {
+                    bound: bound.to_string(),
+                    source,
+                })
+        };
+
+        SectionRange::new(parse_bound(start)?, parse_bound(end)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSectionRangeError {
+    MissingDash(String),
+    InvalidBound {
+        bound: String,
+        source: ParseIntError,
+    },
+    Reversed {
+        start: u32,
+        end: u32,
+    },
+    MissingComma(String),
+}
+
+impl fmt::Display for ParseSectionRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSectionRangeError::MissingDash(s) => {
+                write!(f, "expected a range like `2-4`, got {s:?}")
+            }
+            ParseSectionRangeError::InvalidBound { bound, source } => {
+                write!(f, "invalid section ID {bound:?}: {source}")
+            }
+            ParseSectionRangeError::Reversed { start, end } => {
+                write!(f, "range start {start} is after its end {end}")
+            }
+            ParseSectionRangeError::MissingComma(s) => {
+                write!(f, "expected a pair of ranges like `2-4,6-8`, got {s:?}")
+            }
+        }
+    }
+}
+
+impl Error for ParseSectionRangeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseSectionRangeError::InvalidBound { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Parses one line of the puzzle input, e.g. `2-4,6-8`
+pub fn parse_pair(line: &str) -> Result<(SectionRange, SectionRange), ParseSectionRangeError> {
+    let (range_1, range_2) = line
+        .split_once(',')
+        .ok_or_else(|| ParseSectionRangeError::MissingComma(line.to_string()))?;
+
+    Ok((range_1.parse()?, range_2.parse()?))
+}
@@ -0,0 +1,235 @@
+use std::{fmt, io, str::FromStr};
+
+use anyhow::{anyhow, bail};
+
+use crate::{crate_mover::CrateMover, stacks::Stacks};
+
+// A `move N from A to B` instruction. Stacks are 0-based here and 1-based in the text form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl FromStr for Move {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ["move", count, "from", from, "to", to] = s.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            bail!("Expected `move N from A to B`, got {s:?}");
+        };
+
+        let parse_stack = |stack: &str| -> anyhow::Result<usize> {
+            match stack.parse::<usize>()? {
+                0 => bail!("Stacks are numbered from 1, got 0 in {s:?}"),
+                stack => Ok(stack - 1),
+            }
+        };
+
+        Ok(Move {
+            count: count.parse()?,
+            from: parse_stack(from)?,
+            to: parse_stack(to)?,
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+impl Move {
+    // Checks that the instruction can be executed on `stacks`, whatever the crane model
+    pub fn validate(&self, stacks: &Stacks) -> anyhow::Result<()> {
+        for stack in [self.from, self.to] {
+            if stack >= stacks.len() {
+                bail!(
+                    "{self}: there is no stack {}, only stacks 1 to {}",
+                    stack + 1,
+                    stacks.len()
+                );
+            }
+        }
+
+        let from_stack = &stacks[self.from];
+        if from_stack.len() < self.count {
+            bail!(
+                "{self}: stack {} only holds {} crate(s): {}",
+                self.from + 1,
+                from_stack.len(),
+                describe_stack(from_stack)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn describe_stack(stack: &[char]) -> String {
+    if stack.is_empty() {
+        return String::from("none");
+    }
+
+    let crates: String = stack.iter().collect();
+    format!("{crates} (bottom to top)")
+}
+
+// Parses every procedure line, keeping its line number for reporting
+pub fn parse_procedure(lines: &[(usize, &str)]) -> anyhow::Result<Vec<(usize, Move)>> {
+    lines
+        .iter()
+        .map(|(line_number, line)| {
+            line.parse()
+                .map(|instruction| (*line_number, instruction))
+                .map_err(|error| anyhow!("Line {line_number}: {error}"))
+        })
+        .collect()
+}
+
+// Executes a rearrangement procedure one instruction at a time. Every intermediate state is kept so
+// steps can be undone and any instruction can be jumped to.
+pub struct Crane {
+    crate_mover: Box<dyn CrateMover>,
+    procedure: Vec<(usize, Move)>,
+    // `history[k]` is the state before instruction `k`, so `history.len() - 1` have been executed
+    history: Vec<Stacks>,
+}
+
+impl Crane {
+    pub fn new(
+        crate_mover: Box<dyn CrateMover>,
+        stacks: Stacks,
+        procedure: Vec<(usize, Move)>,
+    ) -> Self {
+        Self {
+            crate_mover,
+            procedure,
+            history: vec![stacks],
+        }
+    }
+
+    pub fn crate_mover(&self) -> &dyn CrateMover {
+        self.crate_mover.as_ref()
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        self.history.last().unwrap()
+    }
+
+    // Number of instructions executed so far
+    pub fn position(&self) -> usize {
+        self.history.len() - 1
+    }
+
+    pub fn procedure(&self) -> &[(usize, Move)] {
+        &self.procedure
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position() == self.procedure.len()
+    }
+
+    // Line number and instruction that the next `step` will execute
+    pub fn next_instruction(&self) -> Option<&(usize, Move)> {
+        self.procedure.get(self.position())
+    }
+
+    // Returns false if the procedure was already finished. Leaves the state untouched if the next
+    // instruction can't be executed.
+    pub fn step(&mut self) -> anyhow::Result<bool> {
+        let Some((line_number, instruction)) = self.next_instruction() else {
+            return Ok(false);
+        };
+
+        let mut stacks = self.stacks().clone();
+        execute(
+            self.crate_mover.as_ref(),
+            &mut stacks,
+            *line_number,
+            instruction,
+        )?;
+        self.history.push(stacks);
+        Ok(true)
+    }
+
+    // Returns false if nothing was executed yet
+    pub fn undo(&mut self) -> bool {
+        if self.position() == 0 {
+            return false;
+        }
+
+        self.history.pop();
+        true
+    }
+
+    pub fn rewind(&mut self) {
+        self.history.truncate(1);
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    // Moves to the state right after the first `position` instructions
+    pub fn jump_to(&mut self, position: usize) -> anyhow::Result<()> {
+        if position > self.procedure.len() {
+            bail!(
+                "Cannot jump to instruction {position}, the procedure only has {}",
+                self.procedure.len()
+            );
+        }
+
+        self.history.truncate(position + 1);
+        while self.position() < position {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    // Writes the initial state and the state after every instruction of the whole procedure,
+    // regardless of the current position. Stops at the first instruction that can't be executed.
+    pub fn write_trace(&self, out: &mut impl io::Write) -> anyhow::Result<()> {
+        let mut stacks = self.history[0].clone();
+        writeln!(out, "Initial state")?;
+        writeln!(out, "{stacks}")?;
+
+        for (step, (line_number, instruction)) in self.procedure.iter().enumerate() {
+            execute(
+                self.crate_mover.as_ref(),
+                &mut stacks,
+                *line_number,
+                instruction,
+            )?;
+            writeln!(out)?;
+            writeln!(out, "Step {} (line {line_number}): {instruction}", step + 1)?;
+            writeln!(out, "{stacks}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn execute(
+    crate_mover: &dyn CrateMover,
+    stacks: &mut Stacks,
+    line_number: usize,
+    instruction: &Move,
+) -> anyhow::Result<()> {
+    instruction
+        .validate(stacks)
+        .map_err(|error| anyhow!("Line {line_number}: {error}"))?;
+    crate_mover.move_crates(stacks, instruction);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, bail};
+
+use crate::{crane::Move, stacks::Stacks};
+
+// A crane model, i.e. how a `move` instruction rearranges the stacks
+pub trait CrateMover {
+    fn name(&self) -> String;
+
+    // `instruction` must already be validated against `stacks` (see `Move::validate`)
+    fn move_crates(&self, stacks: &mut Stacks, instruction: &Move);
+}
+
+// Moves crates one at a time, so the load ends up reversed
+pub struct CrateMover9000;
+
+impl CrateMover for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, instruction: &Move) {
+        for _ in 0..instruction.count {
+            let item = stacks[instruction.from].pop().unwrap();
+            stacks[instruction.to].push(item);
+        }
+    }
+}
+
+// Moves all crates at once, keeping their order
+pub struct CrateMover9001;
+
+impl CrateMover for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, instruction: &Move) {
+        let range_to_move = stacks[instruction.from].len() - instruction.count..;
+        let items: Vec<char> = stacks[instruction.from].drain(range_to_move).collect();
+        stacks[instruction.to].extend(items);
+    }
+}
+
+// Lifts at most `capacity` crates at a time, keeping the order within each lift. A capacity of 1
+// behaves like the 9000, and one at least as large as every move behaves like the 9001.
+pub struct CappedCrateMover {
+    pub capacity: usize,
+}
+
+impl CrateMover for CappedCrateMover {
+    fn name(&self) -> String {
+        format!("capped crane ({} per lift)", self.capacity)
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, instruction: &Move) {
+        let mut remaining = instruction.count;
+        while remaining > 0 {
+            let lift = remaining.min(self.capacity);
+            let range_to_move = stacks[instruction.from].len() - lift..;
+            let items: Vec<char> = stacks[instruction.from].drain(range_to_move).collect();
+            stacks[instruction.to].extend(items);
+            remaining -= lift;
+        }
+    }
+}
+
+// Lifts all crates at once like the 9001, but flips every other crate with the one above it, so a
+// load of `ABCDE` (bottom to top) lands as `BADCE`
+pub struct PairFlippingCrateMover;
+
+impl CrateMover for PairFlippingCrateMover {
+    fn name(&self) -> String {
+        String::from("pair-flipping crane")
+    }
+
+    fn move_crates(&self, stacks: &mut Stacks, instruction: &Move) {
+        let range_to_move = stacks[instruction.from].len() - instruction.count..;
+        let mut items: Vec<char> = stacks[instruction.from].drain(range_to_move).collect();
+        for pair in items.chunks_exact_mut(2) {
+            pair.swap(0, 1);
+        }
+        stacks[instruction.to].extend(items);
+    }
+}
+
+// Parses a model name as given on the command line: `9000`, `9001`, `capped:<K>` or `pair-flip`
+pub fn parse_crate_mover(s: &str) -> anyhow::Result<Box<dyn CrateMover>> {
+    match s.split_once(':') {
+        None if s == "9000" => Ok(Box::new(CrateMover9000)),
+        None if s == "9001" => Ok(Box::new(CrateMover9001)),
+        None if s == "pair-flip" => Ok(Box::new(PairFlippingCrateMover)),
+        Some(("capped", capacity)) => match capacity.parse()? {
+            0 => bail!("A capped crane must be able to lift at least one crate"),
+            capacity => Ok(Box::new(CappedCrateMover { capacity })),
+        },
+        _ => Err(anyhow!(
+            "Unknown crane model {s:?}, expected 9000, 9001, capped:<K> or pair-flip"
+        )),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod crane;
+pub mod crate_mover;
+pub mod planner;
+pub mod stacks;
@@ -1,71 +1,188 @@
+use std::io::{self, BufRead, Write};
+
+use day_05::{
+    crane::{parse_procedure, Crane},
+    crate_mover::{parse_crate_mover, CrateMover9000, CrateMover9001},
+    planner::{plan, Goal},
+    stacks::{split_input, Stacks},
+};
+
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+
     let input = include_str!("../input.txt");
 
-    // Hardcoded initial stack height and number of stacks
-    const INITIAL_STACK_HEIGHT: usize = 8;
-    const NUMBER_OF_STACKS: usize = 9;
+    let (drawing, instruction_lines) = split_input(input)?;
+    let stacks = Stacks::parse_drawing(drawing)?;
+    let procedure = parse_procedure(&instruction_lines)?;
 
-    let mut stacks: Vec<Vec<char>> = vec![vec![]; NUMBER_OF_STACKS];
+    // Run a single crane model instead of both parts
+    let model = option_value("--model")
+        .map(|model| parse_crate_mover(model))
+        .transpose()?;
 
-    let stack_lines = input.lines().take(INITIAL_STACK_HEIGHT).collect::<Vec<_>>();
+    // Inverse problem: find the shortest procedure reaching a goal from the initial drawing (taken
+    // from `--drawing` if given) and print it as a complete puzzle input
+    let goal = match (option_value("--plan-tops"), option_value("--plan-layout")) {
+        (Some(top_crates), _) => Some(Goal::parse_top_crates(top_crates)),
+        (None, Some(layout_path)) => {
+            let layout = std::fs::read_to_string(layout_path)?;
+            Some(Goal::Layout(Stacks::parse_drawing(drawing_part(&layout))?))
+        }
+        (None, None) => None,
+    };
 
-    for level in stack_lines.iter().rev() {
-        for (stack_index, stack) in stacks.iter_mut().enumerate() {
-            let index_in_line = 1 + stack_index * 4;
-            let value = level.chars().nth(index_in_line).unwrap();
-            if value != ' ' {
-                stack.push(value);
+    if let Some(goal) = goal {
+        let model = model.unwrap_or_else(|| Box::new(CrateMover9000));
+        let initial = match option_value("--drawing") {
+            Some(drawing_path) => {
+                let drawing = std::fs::read_to_string(drawing_path)?;
+                Stacks::parse_drawing(drawing_part(&drawing))?
             }
+            None => stacks,
+        };
+        let max_states = match option_value("--max-states") {
+            Some(max_states) => max_states.parse()?,
+            None => 1_000_000,
+        };
+
+        let moves = plan(&initial, &goal, model.as_ref(), max_states)?;
+
+        println!("{initial}");
+        println!();
+        for instruction in moves {
+            println!("{instruction}");
         }
+
+        return Ok(());
     }
 
-    let mut part_1_stacks = stacks.clone();
-    let mut part_2_stacks = stacks;
-
-    let instruction_lines = input
-        .lines()
-        .skip(INITIAL_STACK_HEIGHT + 2)
-        .collect::<Vec<_>>();
-
-    for instruction_line in instruction_lines {
-        let mut split_on_whitespace = instruction_line.split_whitespace();
-        let items_to_move = split_on_whitespace
-            .nth(1)
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-
-        let starting_stack = split_on_whitespace
-            .nth(1)
-            .unwrap()
-            .parse::<usize>()
-            .unwrap()
-            - 1;
-
-        let ending_stack = split_on_whitespace
-            .nth(1)
-            .unwrap()
-            .parse::<usize>()
-            .unwrap()
-            - 1;
-
-        // Instruction execution for part 1
-        for _ in 0..items_to_move {
-            let item = part_1_stacks[starting_stack].pop().unwrap();
-            part_1_stacks[ending_stack].push(item);
-        }
+    if let Some(trace_path) = option_value("--trace") {
+        let model = model.unwrap_or_else(|| Box::new(CrateMover9000));
+        let crane = Crane::new(model, stacks, procedure);
+        let mut trace_file = io::BufWriter::new(std::fs::File::create(trace_path)?);
+        crane.write_trace(&mut trace_file)?;
+        return Ok(());
+    }
 
-        // Instruction execution for part 2
-        let range_to_move = part_2_stacks[starting_stack].len() - items_to_move..;
-        let items: Vec<char> = part_2_stacks[starting_stack].drain(range_to_move).collect();
-        part_2_stacks[ending_stack].extend(items);
+    if args.iter().any(|arg| arg == "--step") {
+        let model = model.unwrap_or_else(|| Box::new(CrateMover9000));
+        let mut crane = Crane::new(model, stacks, procedure);
+        return step_through(&mut crane);
     }
 
-    let part_1: String = part_1_stacks.iter().map(|stack| stack[stack.len() - 1]).collect();
-    let part_2: String = part_2_stacks.iter().map(|stack| stack[stack.len() - 1]).collect();
+    if let Some(model) = model {
+        let mut crane = Crane::new(model, stacks, procedure);
+        crane.run()?;
+
+        println!(
+            "{}: {}",
+            crane.crate_mover().name(),
+            crane.stacks().top_crates_report()
+        );
+
+        return Ok(());
+    }
+
+    let mut part_1_crane = Crane::new(Box::new(CrateMover9000), stacks.clone(), procedure.clone());
+    let mut part_2_crane = Crane::new(Box::new(CrateMover9001), stacks, procedure);
+
+    part_1_crane.run()?;
+    part_2_crane.run()?;
+
+    let part_1 = part_1_crane.stacks().top_crates_report();
+    let part_2 = part_2_crane.stacks().top_crates_report();
 
     println!("Part 1: {part_1}");
     println!("Part 2: {part_2}");
 
     Ok(())
 }
+
+// Drawing files may be a bare drawing or a whole puzzle input
+fn drawing_part(contents: &str) -> &str {
+    match split_input(contents) {
+        Ok((drawing, _)) => drawing,
+        Err(_) => contents.trim_end(),
+    }
+}
+
+// Interactive debugger reading commands from stdin
+fn step_through(crane: &mut Crane) -> anyhow::Result<()> {
+    const HELP: &str = "Commands: [n]ext [count], [u]ndo [count], [g]oto <k>, [r]ewind, [e]nd, \
+                        [s]how, [t]race <file>, [q]uit";
+
+    println!("{}. {HELP}", crane.crate_mover().name());
+    show_state(crane);
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let command: Vec<&str> = line.split_whitespace().collect();
+
+        let repeat = |count: Option<&&str>| -> anyhow::Result<usize> {
+            Ok(count.map(|count| count.parse()).transpose()?.unwrap_or(1))
+        };
+
+        let result = match command[..] {
+            [] => Ok(()),
+            ["n" | "next", ref count @ ..] => repeat(count.first()).and_then(|count| {
+                for _ in 0..count {
+                    crane.step()?;
+                }
+                Ok(())
+            }),
+            ["u" | "undo", ref count @ ..] => repeat(count.first()).map(|count| {
+                for _ in 0..count {
+                    crane.undo();
+                }
+            }),
+            ["g" | "goto", position] => position
+                .parse()
+                .map_err(anyhow::Error::from)
+                .and_then(|position| crane.jump_to(position)),
+            ["r" | "rewind"] => {
+                crane.rewind();
+                Ok(())
+            }
+            ["e" | "end"] => crane.run(),
+            ["s" | "show"] => Ok(()),
+            ["t" | "trace", path] => std::fs::File::create(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|file| crane.write_trace(&mut io::BufWriter::new(file))),
+            ["q" | "quit"] => break,
+            _ => Err(anyhow::anyhow!("Unknown command {line:?}. {HELP}")),
+        };
+
+        match result {
+            Ok(()) => show_state(crane),
+            Err(error) => {
+                eprintln!("{error}");
+                print!("> ");
+                io::stdout().flush()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn show_state(crane: &Crane) {
+    println!();
+    println!("{}", crane.stacks());
+    print!(
+        "After {} of {} instructions",
+        crane.position(),
+        crane.procedure().len()
+    );
+    match crane.next_instruction() {
+        Some((line_number, instruction)) => println!(", next is line {line_number}: {instruction}"),
+        None => println!(", done"),
+    }
+    print!("> ");
+    let _ = io::stdout().flush();
+}
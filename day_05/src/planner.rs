@@ -0,0 +1,287 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+};
+
+use anyhow::bail;
+
+use crate::{crane::Move, crate_mover::CrateMover, stacks::Stacks};
+
+pub enum Goal {
+    // Crate expected on top of every stack, `None` for stacks that must end up empty
+    TopCrates(Vec<Option<char>>),
+    Layout(Stacks),
+}
+
+impl Goal {
+    // Parses a top crates string in the same format as the puzzle answer, with `-` for empty stacks
+    pub fn parse_top_crates(s: &str) -> Self {
+        Goal::TopCrates(
+            s.trim()
+                .chars()
+                .map(|c| if c == '-' { None } else { Some(c) })
+                .collect(),
+        )
+    }
+
+    fn is_reached(&self, stacks: &Stacks) -> bool {
+        self.mismatched_stacks(stacks) == 0
+    }
+
+    fn mismatched_stacks(&self, stacks: &Stacks) -> usize {
+        match self {
+            Goal::TopCrates(top_crates) => stacks
+                .iter()
+                .zip(top_crates)
+                .filter(|(stack, top_crate)| stack.last() != top_crate.as_ref())
+                .count(),
+            Goal::Layout(layout) => stacks
+                .iter()
+                .zip(layout.iter())
+                .filter(|(stack, target)| stack != target)
+                .count(),
+        }
+    }
+
+    // A move changes at most two stacks, so this never overestimates the moves left
+    fn lower_bound(&self, stacks: &Stacks) -> usize {
+        self.mismatched_stacks(stacks).div_ceil(2)
+    }
+
+    // Rejects goals that can't possibly be reached from `initial`, whatever the crane model
+    fn check_reachable(&self, initial: &Stacks) -> anyhow::Result<()> {
+        let crate_counts = |stacks: &[Vec<char>]| {
+            let mut counts: HashMap<char, usize> = HashMap::new();
+            for &value in stacks.iter().flatten() {
+                *counts.entry(value).or_default() += 1;
+            }
+            counts
+        };
+        let available = crate_counts(initial);
+
+        match self {
+            Goal::TopCrates(top_crates) => {
+                if top_crates.len() != initial.len() {
+                    bail!(
+                        "Expected {} top crates, one per stack, got {}",
+                        initial.len(),
+                        top_crates.len()
+                    );
+                }
+
+                let wanted = crate_counts(&[top_crates.iter().flatten().copied().collect()]);
+                for (value, count) in wanted {
+                    let available = available.get(&value).copied().unwrap_or(0);
+                    if available < count {
+                        bail!("{count} stack(s) should end with [{value}] but there are only {available}");
+                    }
+                }
+
+                let total_crates: usize = initial.iter().map(Vec::len).sum();
+                let non_empty_stacks = top_crates.iter().filter(|top| top.is_some()).count();
+                if total_crates < non_empty_stacks {
+                    bail!("{non_empty_stacks} stacks should be non-empty but there are only {total_crates} crates");
+                }
+                if total_crates > 0 && non_empty_stacks == 0 {
+                    bail!("All stacks should end up empty but there are {total_crates} crates");
+                }
+            }
+            Goal::Layout(layout) => {
+                if layout.len() != initial.len() {
+                    bail!(
+                        "The target layout has {} stacks but the initial one has {}",
+                        layout.len(),
+                        initial.len()
+                    );
+                }
+                if crate_counts(layout) != available {
+                    bail!("The target layout doesn't hold the same crates as the initial one");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Finds a shortest sequence of moves that rearranges `initial` to reach `goal` with `crate_mover`,
+// using A* over stack states. Gives up after exploring `max_states` states, since the search space
+// grows very quickly with the number of crates.
+pub fn plan(
+    initial: &Stacks,
+    goal: &Goal,
+    crate_mover: &dyn CrateMover,
+    max_states: usize,
+) -> anyhow::Result<Vec<Move>> {
+    goal.check_reachable(initial)?;
+
+    // Every state seen so far with the move that first reached it from its parent
+    let mut states: Vec<(Stacks, Option<(usize, Move)>)> = vec![(initial.clone(), None)];
+    let mut best_distances: HashMap<Stacks, usize> = HashMap::from([(initial.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((goal.lower_bound(initial), 0, 0))]);
+
+    while let Some(Reverse((_, distance, state_index))) = queue.pop() {
+        let stacks = states[state_index].0.clone();
+
+        // Stale queue entry, a shorter path to this state was found since
+        if best_distances[&stacks] < distance {
+            continue;
+        }
+
+        if goal.is_reached(&stacks) {
+            let mut moves = Vec::new();
+            let mut current = state_index;
+            while let Some((parent, instruction)) = states[current].1 {
+                moves.push(instruction);
+                current = parent;
+            }
+            moves.reverse();
+            return Ok(moves);
+        }
+
+        if states.len() > max_states {
+            bail!("Gave up after exploring {max_states} states without reaching the goal");
+        }
+
+        for from in 0..stacks.len() {
+            for to in (0..stacks.len()).filter(|&to| to != from) {
+                for count in 1..=stacks[from].len() {
+                    let instruction = Move { count, from, to };
+                    let mut next = stacks.clone();
+                    crate_mover.move_crates(&mut next, &instruction);
+
+                    match best_distances.entry(next.clone()) {
+                        Entry::Occupied(entry) if *entry.get() <= distance + 1 => continue,
+                        Entry::Occupied(mut entry) => {
+                            entry.insert(distance + 1);
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(distance + 1);
+                        }
+                    }
+
+                    let estimate = distance + 1 + goal.lower_bound(&next);
+                    queue.push(Reverse((estimate, distance + 1, states.len())));
+                    states.push((next, Some((state_index, instruction))));
+                }
+            }
+        }
+    }
+
+    bail!("The goal can't be reached with the {}", crate_mover.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+    use crate::crate_mover::{CrateMover9000, CrateMover9001};
+
+    // The stacks of the puzzle example
+    fn example() -> Stacks {
+        Stacks::new(vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']])
+    }
+
+    fn moves_from(stacks: &Stacks) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for from in 0..stacks.len() {
+            for to in (0..stacks.len()).filter(|&to| to != from) {
+                for count in 1..=stacks[from].len() {
+                    moves.push(Move { count, from, to });
+                }
+            }
+        }
+        moves
+    }
+
+    // Fewest moves to reach the goal by plain breadth-first search, if it can be reached
+    fn bfs_distance(initial: &Stacks, goal: &Goal, crate_mover: &dyn CrateMover) -> Option<usize> {
+        let mut seen = HashSet::from([initial.clone()]);
+        let mut queue = VecDeque::from([(initial.clone(), 0)]);
+
+        while let Some((stacks, distance)) = queue.pop_front() {
+            if goal.is_reached(&stacks) {
+                return Some(distance);
+            }
+            for instruction in moves_from(&stacks) {
+                let mut next = stacks.clone();
+                crate_mover.move_crates(&mut next, &instruction);
+                if seen.insert(next.clone()) {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Checks that the plan is valid, reaches the goal, and is as short as possible
+    fn assert_shortest(initial: &Stacks, goal: &Goal, crate_mover: &dyn CrateMover) {
+        let moves = plan(initial, goal, crate_mover, 1_000_000).unwrap();
+
+        let mut stacks = initial.clone();
+        for instruction in &moves {
+            instruction.validate(&stacks).unwrap();
+            crate_mover.move_crates(&mut stacks, instruction);
+        }
+        assert!(goal.is_reached(&stacks));
+        assert_eq!(Some(moves.len()), bfs_distance(initial, goal, crate_mover));
+    }
+
+    #[test]
+    fn example_top_crates() {
+        let goal = Goal::parse_top_crates("CMZ");
+        assert_shortest(&example(), &goal, &CrateMover9000);
+        assert_shortest(&example(), &goal, &CrateMover9001);
+
+        let moves = plan(&example(), &goal, &CrateMover9000, 1_000_000).unwrap();
+        assert_eq!(moves.len(), 2);
+    }
+
+    #[test]
+    fn matches_bfs_on_random_goals() {
+        let mut state = 2022u64;
+        let mut next = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize % bound
+        };
+
+        for crate_mover in [&CrateMover9000 as &dyn CrateMover, &CrateMover9001] {
+            for _ in 0..30 {
+                // A random walk from the example gives a goal that can be reached
+                let mut target = example();
+                for _ in 0..1 + next(5) {
+                    let moves = moves_from(&target);
+                    crate_mover.move_crates(&mut target, &moves[next(moves.len())]);
+                }
+
+                let top_crates = Goal::TopCrates(target.top_crates());
+                assert_shortest(&example(), &top_crates, crate_mover);
+                assert_shortest(&example(), &Goal::Layout(target), crate_mover);
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        // Moving both crates at once with the 9000 reverses them, and there's no way to reorder
+        // them on a single other stack
+        let initial = Stacks::new(vec![vec!['A', 'B'], vec![]]);
+        let goal = Goal::Layout(Stacks::new(vec![vec!['B', 'A'], vec![]]));
+        assert_eq!(bfs_distance(&initial, &goal, &CrateMover9000), None);
+        assert!(plan(&initial, &goal, &CrateMover9000, 1_000_000).is_err());
+
+        let goal = Goal::parse_top_crates("XZ");
+        assert!(plan(&example(), &goal, &CrateMover9000, 1_000_000).is_err());
+    }
+
+    #[test]
+    fn gives_up_after_max_states() {
+        let goal = Goal::parse_top_crates("CMZ");
+        let error = plan(&example(), &goal, &CrateMover9000, 5).unwrap_err();
+        assert!(error.to_string().starts_with("Gave up"));
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use anyhow::{anyhow, bail};
+
+// Crate stacks, bottom crate first. Stack `i` is labelled `i + 1` in the drawing and instructions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stacks(Vec<Vec<char>>);
+
+impl Stacks {
+    pub fn new(stacks: Vec<Vec<char>>) -> Self {
+        Self(stacks)
+    }
+
+    // Parses a drawing such as:
+    //
+    //     [D]
+    // [N] [C]
+    // [Z] [M] [P]
+    //  1   2   3
+    //
+    // The last line holds the stack labels, which must be `1` to `n` from left to right and may
+    // span several digits. A crate belongs to the stack whose label sits under its letter. Lines
+    // may be ragged or have trailing whitespace trimmed.
+    pub fn parse_drawing(drawing: &str) -> anyhow::Result<Self> {
+        let lines: Vec<&str> = drawing.lines().collect();
+        let Some((label_line, crate_lines)) = lines.split_last() else {
+            bail!("Empty drawing");
+        };
+
+        // Column span and number of every label, left to right
+        let mut labels: Vec<(usize, usize, usize)> = Vec::new();
+        let mut label_start = None;
+        for (column, c) in label_line.chars().chain([' ']).enumerate() {
+            match (c.is_ascii_digit(), label_start) {
+                (true, None) => label_start = Some(column),
+                (false, Some(start)) => {
+                    let label: String = label_line
+                        .chars()
+                        .skip(start)
+                        .take(column - start)
+                        .collect();
+                    labels.push((start, column - 1, label.parse()?));
+                    label_start = None;
+                }
+                (false, None) if !c.is_whitespace() => {
+                    bail!(
+                        "Expected stack labels on the last line of the drawing, found {c:?} in {label_line:?}"
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if labels.is_empty() {
+            bail!("No stack labels on the last line of the drawing: {label_line:?}");
+        }
+
+        for (index, (_, _, label)) in labels.iter().enumerate() {
+            if *label != index + 1 {
+                bail!(
+                    "Stack labels must be numbered 1 to {} from left to right, found {label} in position {}",
+                    labels.len(),
+                    index + 1
+                );
+            }
+        }
+
+        let mut stacks = vec![Vec::new(); labels.len()];
+
+        // Build stacks from the bottom up so floating crates can be detected
+        for (height, (line_index, line)) in crate_lines.iter().enumerate().rev().enumerate() {
+            let line_number = line_index + 1;
+            let chars: Vec<char> = line.chars().collect();
+
+            let mut column = 0;
+            while column < chars.len() {
+                match chars[column] {
+                    c if c.is_whitespace() => column += 1,
+                    '[' => {
+                        let (Some(&value), Some(']')) =
+                            (chars.get(column + 1), chars.get(column + 2))
+                        else {
+                            bail!(
+                                "Malformed crate on line {line_number}, column {}: {line:?}",
+                                column + 1
+                            );
+                        };
+                        if value.is_whitespace() || value == ']' {
+                            bail!(
+                                "Empty crate on line {line_number}, column {}: {line:?}",
+                                column + 1
+                            );
+                        }
+
+                        let value_column = column + 1;
+                        let stack_index = labels
+                            .iter()
+                            .position(|(start, end, _)| (*start..=*end).contains(&value_column))
+                            .ok_or_else(|| {
+                                anyhow!(
+                                    "Crate [{value}] on line {line_number}, column {} is not above any stack label",
+                                    column + 1
+                                )
+                            })?;
+
+                        let stack = &mut stacks[stack_index];
+                        if stack.len() != height {
+                            bail!(
+                                "Crate [{value}] on line {line_number} is floating above an empty space in stack {}",
+                                stack_index + 1
+                            );
+                        }
+                        stack.push(value);
+
+                        column += 3;
+                    }
+                    c => bail!(
+                        "Unexpected character {c:?} on line {line_number}, column {}: {line:?}",
+                        column + 1
+                    ),
+                }
+            }
+        }
+
+        Ok(Self(stacks))
+    }
+
+    // Crate on top of every stack, `None` for empty stacks
+    pub fn top_crates(&self) -> Vec<Option<char>> {
+        self.0.iter().map(|stack| stack.last().copied()).collect()
+    }
+
+    // Top crates as a string, e.g. `CMZ`. Empty stacks show as `-` and are listed after the string.
+    pub fn top_crates_report(&self) -> String {
+        let top_crates = self.top_crates();
+        let report: String = top_crates
+            .iter()
+            .map(|top_crate| top_crate.unwrap_or('-'))
+            .collect();
+
+        let empty_stacks: Vec<String> = top_crates
+            .iter()
+            .enumerate()
+            .filter(|(_, top_crate)| top_crate.is_none())
+            .map(|(index, _)| (index + 1).to_string())
+            .collect();
+
+        if empty_stacks.is_empty() {
+            report
+        } else {
+            format!("{report} (empty stacks: {})", empty_stacks.join(", "))
+        }
+    }
+
+    pub fn into_inner(self) -> Vec<Vec<char>> {
+        self.0
+    }
+}
+
+impl Deref for Stacks {
+    type Target = [Vec<char>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Stacks {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// Renders the stacks in the same format as the puzzle drawing
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+
+        for level in (0..height).rev() {
+            let line: Vec<String> = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(value) => format!("[{value}]"),
+                    None => String::from("   "),
+                })
+                .collect();
+            writeln!(f, "{}", line.join(" ").trim_end())?;
+        }
+
+        let labels: Vec<String> = (1..=self.0.len())
+            .map(|label| format!("{label:^3}"))
+            .collect();
+        write!(f, "{}", labels.join(" ").trim_end())
+    }
+}
+
+// Splits the puzzle input on its first blank line into the drawing and the rearrangement procedure.
+// Procedure lines come with their 1-based line number in the input; blank ones are skipped.
+pub fn split_input(input: &str) -> anyhow::Result<(&str, Vec<(usize, &str)>)> {
+    let mut offset = 0;
+    for (line_index, line) in input.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            let drawing = &input[..offset];
+            let procedure = input[offset + line.len()..]
+                .lines()
+                .enumerate()
+                .map(|(index, instruction)| (line_index + index + 2, instruction))
+                .filter(|(_, instruction)| !instruction.trim().is_empty())
+                .collect();
+            return Ok((drawing, procedure));
+        }
+        offset += line.len();
+    }
+
+    bail!("Expected a blank line between the drawing and the rearrangement procedure")
+}
@@ -1,38 +1,114 @@
+use std::io::{self, BufWriter, Read, Write};
+
+use day_06::{
+    marker::{find_first_marker, AlphabetKind, AnyByte, AnyChar, LowercaseAscii},
+    stream::{scan_bytes, scan_chars},
+};
+
 fn main() -> anyhow::Result<()> {
-    let input = include_str!("../input.txt");
-    let input_chars = input.bytes().collect::<Vec<_>>();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
 
-    // Part 1
-    for (index, window) in input_chars.windows(4).enumerate() {
-        // Represent the presence of each character in the window as a bit in a u32 (pos = 1 << position in alphabet)
-        let mut bits = 0u32;
-        for c in window {
-            bits |= 1 << (c - b'a');
-        }
-        if bits.count_ones() == 4 {
-            println!("Part 1: {}", index + 4);
-            break;
+    // Report every marker in a datastream read from a file (or stdin for `-`)
+    if let Some(path) = option_value("--scan") {
+        let window_size = match option_value("--window") {
+            Some(window_size) => window_size.parse()?,
+            None => 4,
+        };
+        let alphabet = match option_value("--alphabet") {
+            Some(alphabet) => alphabet.parse()?,
+            None => AlphabetKind::LowercaseAscii,
+        };
+
+        let reader: Box<dyn Read> = match path.as_str() {
+            "-" => Box::new(io::stdin().lock()),
+            path => Box::new(std::fs::File::open(path)?),
+        };
+
+        return scan(reader, window_size, alphabet);
+    }
+
+    let input = include_str!("../input.txt").trim_end();
+
+    // Custom marker size and alphabet instead of both parts
+    if let Some(window_size) = option_value("--window") {
+        let window_size = window_size.parse()?;
+        let alphabet = match option_value("--alphabet") {
+            Some(alphabet) => alphabet.parse()?,
+            None => AlphabetKind::LowercaseAscii,
+        };
+
+        let marker = match alphabet {
+            AlphabetKind::LowercaseAscii => {
+                find_first_marker(LowercaseAscii, window_size, input.bytes())?
+            }
+            AlphabetKind::AnyByte => find_first_marker(AnyByte, window_size, input.bytes())?,
+            AlphabetKind::AnyChar => find_first_marker(AnyChar, window_size, input.chars())?,
+        };
+
+        match marker {
+            Some(marker) => println!("Marker of size {window_size}: {marker}"),
+            None => println!("No marker of size {window_size}"),
         }
+
+        return Ok(());
     }
 
+    // Part 1
+    let part_1 = find_first_marker(LowercaseAscii, 4, input.bytes())?
+        .ok_or_else(|| anyhow::anyhow!("No start-of-packet marker"))?;
+    println!("Part 1: {part_1}");
+
     // Part 2
-    // Represent the number of each character in the window as an array of u8
-    let mut counts = [0u8; 26];
-    for index in 0..input_chars.len() {
-        // Decrement the count of the character leaving the window
-        if index >= 14 {
-            counts[(input_chars[index - 14] - b'a') as usize] -= 1;
-        }
+    let part_2 = find_first_marker(LowercaseAscii, 14, input.bytes())?
+        .ok_or_else(|| anyhow::anyhow!("No start-of-message marker"))?;
+    println!("Part 2: {part_2}");
+
+    Ok(())
+}
 
-        // Increment the count of the character entering the window
-        counts[(input_chars[index] - b'a') as usize] += 1;
+fn scan(reader: impl Read, window_size: usize, alphabet: AlphabetKind) -> anyhow::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut markers = 0;
+    let mut write_error = None;
 
-        // Check if the window contains 14 distinct characters
-        if counts.iter().filter(|&&c| c > 0).count() == 14 {
-            println!("Part 2: {}", index + 1);
-            break;
+    let mut report = |position: usize, marker: String| {
+        markers += 1;
+        if write_error.is_none() {
+            write_error = writeln!(out, "{position}: {marker:?}").err();
         }
+    };
+
+    let processed = match alphabet {
+        AlphabetKind::LowercaseAscii => scan_bytes(LowercaseAscii, window_size, reader, |found| {
+            report(
+                found.position,
+                String::from_utf8_lossy(&found.marker).into_owned(),
+            )
+        })?,
+        AlphabetKind::AnyByte => scan_bytes(AnyByte, window_size, reader, |found| {
+            report(
+                found.position,
+                String::from_utf8_lossy(&found.marker).into_owned(),
+            )
+        })?,
+        AlphabetKind::AnyChar => scan_chars(AnyChar, window_size, reader, |found| {
+            report(found.position, found.marker.into_iter().collect())
+        })?,
+    };
+
+    if let Some(error) = write_error {
+        return Err(error.into());
     }
 
+    writeln!(
+        out,
+        "{markers} marker(s) of size {window_size} in {processed} symbols"
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,232 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+
+// A byte or char of a datastream
+pub trait StreamSymbol: Copy + Debug {
+    // Quoted and escaped for error messages, e.g. '\n'
+    fn escaped(self) -> String;
+}
+
+impl StreamSymbol for u8 {
+    fn escaped(self) -> String {
+        format!("'{}'", std::ascii::escape_default(self))
+    }
+}
+
+impl StreamSymbol for char {
+    fn escaped(self) -> String {
+        format!("{self:?}")
+    }
+}
+
+// Set of symbols a datastream may contain, each mapped to a distinct index
+pub trait Alphabet {
+    type Symbol: StreamSymbol;
+
+    // `None` if the symbol isn't part of the alphabet
+    fn index(&self, symbol: Self::Symbol) -> Option<usize>;
+
+    // Number of symbols in the alphabet
+    fn size(&self) -> usize;
+}
+
+// `a` to `z`, as in the puzzle
+pub struct LowercaseAscii;
+
+impl Alphabet for LowercaseAscii {
+    type Symbol = u8;
+
+    fn index(&self, symbol: u8) -> Option<usize> {
+        symbol
+            .is_ascii_lowercase()
+            .then(|| (symbol - b'a') as usize)
+    }
+
+    fn size(&self) -> usize {
+        26
+    }
+}
+
+pub struct AnyByte;
+
+impl Alphabet for AnyByte {
+    type Symbol = u8;
+
+    fn index(&self, symbol: u8) -> Option<usize> {
+        Some(symbol as usize)
+    }
+
+    fn size(&self) -> usize {
+        256
+    }
+}
+
+// Every Unicode scalar value
+pub struct AnyChar;
+
+impl Alphabet for AnyChar {
+    type Symbol = char;
+
+    fn index(&self, symbol: char) -> Option<usize> {
+        Some(symbol as usize)
+    }
+
+    fn size(&self) -> usize {
+        char::MAX as usize + 1
+    }
+}
+
+// Alphabets selectable from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphabetKind {
+    LowercaseAscii,
+    AnyByte,
+    AnyChar,
+}
+
+impl FromStr for AlphabetKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lowercase" => Ok(AlphabetKind::LowercaseAscii),
+            "bytes" => Ok(AlphabetKind::AnyByte),
+            "chars" => Ok(AlphabetKind::AnyChar),
+            _ => Err(anyhow!(
+                "Unknown alphabet {s:?}, expected lowercase, bytes or chars"
+            )),
+        }
+    }
+}
+
+// Above this many symbols, counts are kept in a map rather than an array
+const MAX_DENSE_ALPHABET_SIZE: usize = 1 << 16;
+
+enum Counts {
+    Dense(Vec<u32>),
+    Sparse(HashMap<usize, u32>),
+}
+
+impl Counts {
+    // Adds `delta` to the count of `index` and returns the new count
+    fn add(&mut self, index: usize, delta: i32) -> u32 {
+        let count = match self {
+            Counts::Dense(counts) => &mut counts[index],
+            Counts::Sparse(counts) => counts.entry(index).or_default(),
+        };
+        *count = count.checked_add_signed(delta).unwrap();
+        let count = *count;
+
+        if let Counts::Sparse(counts) = self {
+            if count == 0 {
+                counts.remove(&index);
+            }
+        }
+
+        count
+    }
+}
+
+// Sliding window over a datastream that tracks how many distinct symbols it holds, updated in O(1)
+// per symbol. A marker is a full window of pairwise distinct symbols.
+pub struct MarkerDetector<A: Alphabet> {
+    alphabet: A,
+    window_size: usize,
+    window: VecDeque<(usize, A::Symbol)>,
+    counts: Counts,
+    distinct: usize,
+    position: usize,
+}
+
+impl<A: Alphabet> MarkerDetector<A> {
+    pub fn new(alphabet: A, window_size: usize) -> anyhow::Result<Self> {
+        if window_size == 0 {
+            bail!("The marker size must be at least 1");
+        }
+        if window_size > alphabet.size() {
+            bail!(
+                "A marker of {window_size} distinct symbols can't exist in an alphabet of {}",
+                alphabet.size()
+            );
+        }
+
+        let counts = if alphabet.size() <= MAX_DENSE_ALPHABET_SIZE {
+            Counts::Dense(vec![0; alphabet.size()])
+        } else {
+            Counts::Sparse(HashMap::new())
+        };
+
+        Ok(Self {
+            alphabet,
+            window_size,
+            window: VecDeque::with_capacity(window_size + 1),
+            counts,
+            distinct: 0,
+            position: 0,
+        })
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    // Symbols currently in the window, oldest first
+    pub fn window(&self) -> impl Iterator<Item = A::Symbol> + '_ {
+        self.window.iter().map(|(_, symbol)| *symbol)
+    }
+
+    // Number of symbols pushed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Slides the window over `symbol` and returns whether it now holds a marker. Symbols outside
+    // the alphabet are rejected without changing the window.
+    pub fn push(&mut self, symbol: A::Symbol) -> anyhow::Result<bool> {
+        let Some(index) = self.alphabet.index(symbol) else {
+            bail!(
+                "Symbol {} at position {} is not part of the alphabet",
+                symbol.escaped(),
+                self.position + 1
+            );
+        };
+
+        self.position += 1;
+
+        // Symbol entering the window
+        if self.counts.add(index, 1) == 1 {
+            self.distinct += 1;
+        }
+        self.window.push_back((index, symbol));
+
+        // Symbol leaving the window
+        if self.window.len() > self.window_size {
+            let (leaving, _) = self.window.pop_front().unwrap();
+            if self.counts.add(leaving, -1) == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        Ok(self.distinct == self.window_size)
+    }
+}
+
+// Number of symbols processed when the first marker completes, if there is one
+pub fn find_first_marker<A: Alphabet>(
+    alphabet: A,
+    window_size: usize,
+    symbols: impl IntoIterator<Item = A::Symbol>,
+) -> anyhow::Result<Option<usize>> {
+    let mut detector = MarkerDetector::new(alphabet, window_size)?;
+    for symbol in symbols {
+        if detector.push(symbol)? {
+            return Ok(Some(detector.position()));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,291 @@
+use std::io::{ErrorKind, Read};
+
+use anyhow::bail;
+
+use crate::marker::{Alphabet, MarkerDetector};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub struct MarkerMatch<S> {
+    // Number of symbols processed when the marker completes, as in the puzzle answer
+    pub position: usize,
+    pub marker: Vec<S>,
+}
+
+// Feeds a datastream to a detector piece by piece and reports every marker, including overlapping
+// ones. Line breaks are held back until more data follows them, so captured streams ending with a
+// newline scan the same as without it, whatever the alphabet.
+pub struct MarkerScanner<A: Alphabet> {
+    detector: MarkerDetector<A>,
+    line_breaks: Vec<A::Symbol>,
+}
+
+impl<A: Alphabet> MarkerScanner<A>
+where
+    A::Symbol: Into<u32>,
+{
+    pub fn new(alphabet: A, window_size: usize) -> anyhow::Result<Self> {
+        Ok(Self {
+            detector: MarkerDetector::new(alphabet, window_size)?,
+            line_breaks: Vec::new(),
+        })
+    }
+
+    pub fn feed(
+        &mut self,
+        symbols: impl IntoIterator<Item = A::Symbol>,
+        on_marker: &mut impl FnMut(MarkerMatch<A::Symbol>),
+    ) -> anyhow::Result<()> {
+        for symbol in symbols {
+            let code: u32 = symbol.into();
+            if code == '\n' as u32 || code == '\r' as u32 {
+                self.line_breaks.push(symbol);
+                continue;
+            }
+
+            // Line breaks followed by data are part of the stream
+            for line_break in std::mem::take(&mut self.line_breaks) {
+                self.push(line_break, on_marker)?;
+            }
+            self.push(symbol, on_marker)?;
+        }
+
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        symbol: A::Symbol,
+        on_marker: &mut impl FnMut(MarkerMatch<A::Symbol>),
+    ) -> anyhow::Result<()> {
+        if self.detector.push(symbol)? {
+            on_marker(MarkerMatch {
+                position: self.detector.position(),
+                marker: self.detector.window().collect(),
+            });
+        }
+        Ok(())
+    }
+
+    // Number of symbols processed, not counting trailing line breaks
+    pub fn position(&self) -> usize {
+        self.detector.position()
+    }
+}
+
+// Reads into `buffer` until it's full or the source is exhausted, returning how much was read
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> anyhow::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(filled)
+}
+
+// Scans a byte stream in fixed-size chunks, returning the number of bytes processed
+pub fn scan_bytes<A: Alphabet<Symbol = u8>>(
+    alphabet: A,
+    window_size: usize,
+    reader: impl Read,
+    on_marker: impl FnMut(MarkerMatch<u8>),
+) -> anyhow::Result<usize> {
+    scan_bytes_in_chunks(alphabet, window_size, reader, on_marker, CHUNK_SIZE)
+}
+
+fn scan_bytes_in_chunks<A: Alphabet<Symbol = u8>>(
+    alphabet: A,
+    window_size: usize,
+    mut reader: impl Read,
+    mut on_marker: impl FnMut(MarkerMatch<u8>),
+    chunk_size: usize,
+) -> anyhow::Result<usize> {
+    let mut scanner = MarkerScanner::new(alphabet, window_size)?;
+    let mut buffer = vec![0; chunk_size];
+
+    loop {
+        let read = read_chunk(&mut reader, &mut buffer)?;
+        if read == 0 {
+            return Ok(scanner.position());
+        }
+        scanner.feed(buffer[..read].iter().copied(), &mut on_marker)?;
+    }
+}
+
+// Scans a UTF-8 stream in fixed-size chunks, returning the number of chars processed. Chars split
+// across chunks are carried over to the next one.
+pub fn scan_chars<A: Alphabet<Symbol = char>>(
+    alphabet: A,
+    window_size: usize,
+    reader: impl Read,
+    on_marker: impl FnMut(MarkerMatch<char>),
+) -> anyhow::Result<usize> {
+    scan_chars_in_chunks(alphabet, window_size, reader, on_marker, CHUNK_SIZE)
+}
+
+fn scan_chars_in_chunks<A: Alphabet<Symbol = char>>(
+    alphabet: A,
+    window_size: usize,
+    mut reader: impl Read,
+    mut on_marker: impl FnMut(MarkerMatch<char>),
+    chunk_size: usize,
+) -> anyhow::Result<usize> {
+    let mut scanner = MarkerScanner::new(alphabet, window_size)?;
+    // Room for a chunk after the (at most 3) bytes of an incomplete char
+    let mut buffer = vec![0; chunk_size + 3];
+    let mut carried_over = 0;
+    let mut bytes_processed = 0;
+
+    loop {
+        let read = read_chunk(
+            &mut reader,
+            &mut buffer[carried_over..carried_over + chunk_size],
+        )?;
+        let available = carried_over + read;
+
+        let (valid, rest) = match std::str::from_utf8(&buffer[..available]) {
+            Ok(valid) => (valid, 0),
+            // Incomplete char at the end of the chunk, which more data may complete
+            Err(error) if error.error_len().is_none() && read > 0 => {
+                let valid_up_to = error.valid_up_to();
+                let valid = std::str::from_utf8(&buffer[..valid_up_to]).unwrap();
+                (valid, available - valid_up_to)
+            }
+            Err(error) => bail!(
+                "Invalid UTF-8 at byte {}",
+                bytes_processed + error.valid_up_to() + 1
+            ),
+        };
+
+        scanner.feed(valid.chars(), &mut on_marker)?;
+        bytes_processed += valid.len();
+
+        if read == 0 {
+            return Ok(scanner.position());
+        }
+
+        buffer.copy_within(available - rest..available, 0);
+        carried_over = rest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marker::{AnyByte, AnyChar, LowercaseAscii};
+
+    // Markers found, by position, and number of symbols processed
+    type Scan<M> = (Vec<(usize, M)>, usize);
+
+    // Scans with every chunk size up to the length of the input, which must all agree
+    fn scan_all_chunk_sizes<A: Alphabet<Symbol = u8>>(
+        alphabet: impl Fn() -> A,
+        window_size: usize,
+        input: &[u8],
+    ) -> anyhow::Result<Scan<Vec<u8>>> {
+        let mut results = Vec::new();
+        for chunk_size in 1..=input.len().max(1) {
+            let mut markers = Vec::new();
+            let processed = scan_bytes_in_chunks(
+                alphabet(),
+                window_size,
+                input,
+                |found| markers.push((found.position, found.marker)),
+                chunk_size,
+            )?;
+            results.push((markers, processed));
+        }
+        results.dedup();
+        assert_eq!(results.len(), 1, "results depend on the chunk size");
+        Ok(results.pop().unwrap())
+    }
+
+    fn scan_chars_all_chunk_sizes(
+        window_size: usize,
+        input: &[u8],
+    ) -> anyhow::Result<Scan<String>> {
+        let mut results = Vec::new();
+        for chunk_size in 1..=input.len().max(1) {
+            let mut markers = Vec::new();
+            let processed = scan_chars_in_chunks(
+                AnyChar,
+                window_size,
+                input,
+                |found| markers.push((found.position, found.marker.into_iter().collect())),
+                chunk_size,
+            )?;
+            results.push((markers, processed));
+        }
+        results.dedup();
+        assert_eq!(results.len(), 1, "results depend on the chunk size");
+        Ok(results.pop().unwrap())
+    }
+
+    #[test]
+    fn chars_split_across_chunks() {
+        let (markers, processed) = scan_chars_all_chunk_sizes(2, "aé😀😀b".as_bytes()).unwrap();
+        assert_eq!(processed, 5);
+        assert_eq!(
+            markers,
+            [
+                (2, String::from("aé")),
+                (3, String::from("é😀")),
+                (5, String::from("😀b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_final_char() {
+        let input = &"ab😀".as_bytes()[..4];
+        for chunk_size in 1..=input.len() {
+            let error = scan_chars_in_chunks(AnyChar, 2, input, |_| {}, chunk_size).unwrap_err();
+            assert_eq!(error.to_string(), "Invalid UTF-8 at byte 3");
+        }
+    }
+
+    #[test]
+    fn trailing_line_breaks_are_not_data() {
+        for input in [&b"abcd"[..], b"abcd\n", b"abcd\r\n", b"abcd\n\n"] {
+            let expected = (vec![(4, b"abcd".to_vec())], 4);
+            assert_eq!(
+                scan_all_chunk_sizes(|| LowercaseAscii, 4, input).unwrap(),
+                expected
+            );
+            assert_eq!(
+                scan_all_chunk_sizes(|| AnyByte, 4, input).unwrap(),
+                expected
+            );
+
+            let (markers, processed) = scan_chars_all_chunk_sizes(4, input).unwrap();
+            assert_eq!((markers, processed), (vec![(4, String::from("abcd"))], 4));
+        }
+    }
+
+    #[test]
+    fn line_breaks_followed_by_data() {
+        // Outside the alphabet, a line break is an error once more data follows it
+        let error = scan_all_chunk_sizes(|| LowercaseAscii, 4, b"abcd\nefgh\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Symbol '\\n' at position 5 is not part of the alphabet"
+        );
+
+        // Otherwise it's data like any other symbol
+        let (markers, processed) = scan_all_chunk_sizes(|| AnyByte, 4, b"abc\nabc\n").unwrap();
+        assert_eq!(processed, 7);
+        assert_eq!(
+            markers,
+            [
+                (4, b"abc\n".to_vec()),
+                (5, b"bc\na".to_vec()),
+                (6, b"c\nab".to_vec()),
+                (7, b"\nabc".to_vec()),
+            ]
+        );
+    }
+}
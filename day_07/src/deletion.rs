@@ -0,0 +1,432 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+
+use crate::file_system::FileSystem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    DeletedBytes,
+    Deletions, // Then fewest deleted bytes among plans with as few deletions
+}
+
+impl FromStr for Objective {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes" => Ok(Objective::DeletedBytes),
+            "deletions" => Ok(Objective::Deletions),
+            _ => Err(anyhow!(
+                "Unknown objective {s:?}, expected bytes or deletions"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionPlan {
+    pub directories: Vec<usize>,
+    pub deleted_bytes: usize,
+    // Whether no other plan meets the objective better. Plans for very large sizes are searched
+    // at a coarser granularity and may delete a little more than needed.
+    pub optimal: bool,
+}
+
+// Bytes that must be freed so that `required_free` bytes are unused on a disk of `disk_size`
+pub fn bytes_to_free(
+    file_system: &FileSystem,
+    disk_size: usize,
+    required_free: usize,
+) -> anyhow::Result<usize> {
+    let used = file_system.objects[file_system.root].size;
+    if used > disk_size {
+        bail!("The file system holds {used} bytes, more than the disk size of {disk_size}");
+    }
+    if required_free > disk_size {
+        bail!("Cannot free {required_free} bytes on a disk of {disk_size}");
+    }
+
+    Ok(required_free.saturating_sub(disk_size - used))
+}
+
+// Most memory the fewest bytes search may use. Sizes are scaled down to stay within it.
+const MEMORY_LIMIT: usize = 1 << 27;
+
+// Chooses directories, none inside another, whose deletion frees at least `target` bytes. Sizes
+// must be resolved.
+pub fn plan_deletions(
+    file_system: &FileSystem,
+    target: usize,
+    objective: Objective,
+) -> anyhow::Result<Option<DeletionPlan>> {
+    plan_deletions_within(file_system, target, objective, MEMORY_LIMIT)
+}
+
+fn plan_deletions_within(
+    file_system: &FileSystem,
+    target: usize,
+    objective: Objective,
+    memory_limit: usize,
+) -> anyhow::Result<Option<DeletionPlan>> {
+    if target == 0 {
+        return Ok(Some(DeletionPlan {
+            directories: Vec::new(),
+            deleted_bytes: 0,
+            optimal: true,
+        }));
+    }
+
+    // A single deletion always suffices when anything does, since the root may be deleted, so the
+    // smallest directory that is large enough answers `Deletions` and bounds `DeletedBytes`
+    let directories = directories_in_pre_order(file_system);
+    let Some(smallest) = directories
+        .iter()
+        .map(|&(directory, _)| directory)
+        .filter(|&directory| file_system.objects[directory].size >= target)
+        .min_by_key(|&directory| file_system.objects[directory].size)
+    else {
+        return Ok(None);
+    };
+    let single = DeletionPlan {
+        directories: vec![smallest],
+        deleted_bytes: file_system.objects[smallest].size,
+        optimal: true,
+    };
+
+    match objective {
+        Objective::Deletions => Ok(Some(single)),
+        Objective::DeletedBytes => {
+            plan_fewest_bytes(file_system, &directories, target, single, memory_limit).map(Some)
+        }
+    }
+}
+
+// Every directory with the position just past its subtree, in pre-order
+fn directories_in_pre_order(file_system: &FileSystem) -> Vec<(usize, usize)> {
+    let mut directories: Vec<(usize, usize)> = Vec::new();
+    // Indices in `directories` of the directories whose subtree is being visited
+    let mut open = Vec::new();
+    let mut stack = vec![(file_system.root, false)];
+
+    while let Some((directory, children_visited)) = stack.pop() {
+        if children_visited {
+            let index: usize = open.pop().unwrap();
+            directories[index].1 = directories.len();
+            continue;
+        }
+
+        open.push(directories.len());
+        directories.push((directory, 0));
+        stack.push((directory, true));
+        for &child in file_system.objects[directory]
+            .children
+            .iter()
+            .flatten()
+            .rev()
+        {
+            if file_system.objects[child].is_directory() {
+                stack.push((child, false));
+            }
+        }
+    }
+
+    directories
+}
+
+// Subset sum over the directories in pre-order: at each position, the directory there is either
+// deleted, which skips its subtree, or kept, which moves on to its first subdirectory. A sum
+// reachable at a position stays reachable at every later one, so each sum only records the first
+// position it was reached at, which is enough to walk a plan back. Sums never need to exceed the
+// size of the single directory plan.
+//
+// That takes memory for every possible sum, so when sizes are too large they are divided by a
+// scale, rounding down, and the target by the same scale, rounding up: any plan found still frees
+// enough, but may not be the best.
+fn plan_fewest_bytes(
+    file_system: &FileSystem,
+    directories: &[(usize, usize)],
+    target: usize,
+    single: DeletionPlan,
+    memory_limit: usize,
+) -> anyhow::Result<DeletionPlan> {
+    if u32::try_from(directories.len()).is_err() {
+        bail!("Too many directories to plan deletions");
+    }
+
+    // Each sum takes a `u32` first position, and a bit in the current sums and in every pending
+    // set of sums
+    let bytes_per_sum = 4 + (max_pending(directories) + 1).div_ceil(8);
+    let max_width = (memory_limit / bytes_per_sum).max(1);
+    let scale = (single.deleted_bytes + 1).div_ceil(max_width).max(1);
+
+    let size = |position: usize| file_system.objects[directories[position].0].size / scale;
+    let target_sum = target.div_ceil(scale);
+    let max_sum = single.deleted_bytes / scale;
+    if target_sum > max_sum {
+        return Ok(DeletionPlan {
+            optimal: false,
+            ..single
+        });
+    }
+
+    let width = max_sum + 1;
+    let mut reachable = vec![0u64; width.div_ceil(64)];
+    reachable[0] = 1;
+    let mut first_reached = vec![u32::MAX; width];
+    first_reached[0] = 0;
+
+    // Sums reached by deleting a directory, waiting for the position past its subtree
+    let mut pending: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+
+    for (position, &(_, end)) in directories.iter().enumerate() {
+        let skipped = pending
+            .entry(end)
+            .or_insert_with(|| vec![0; reachable.len()]);
+        or_shifted(skipped, &reachable, size(position), width);
+
+        let Some(arriving) = pending.remove(&(position + 1)) else {
+            continue;
+        };
+        for (word_index, (word, arriving_word)) in reachable.iter_mut().zip(arriving).enumerate() {
+            let mut new_bits = arriving_word & !*word;
+            *word |= arriving_word;
+            while new_bits != 0 {
+                let sum = word_index * 64 + new_bits.trailing_zeros() as usize;
+                first_reached[sum] = (position + 1) as u32;
+                new_bits &= new_bits - 1;
+            }
+        }
+    }
+
+    // The single directory plan is in range unless scaling rounded it out
+    let Some(best_sum) =
+        (target_sum..=max_sum).find(|&sum| reachable[sum / 64] & (1 << (sum % 64)) != 0)
+    else {
+        return Ok(DeletionPlan {
+            optimal: false,
+            ..single
+        });
+    };
+
+    // A sum first reached at a position came from deleting a directory whose subtree ends there,
+    // on top of a sum already reachable at that directory
+    let mut plan = Vec::new();
+    let mut sum = best_sum;
+    while sum > 0 {
+        let position = first_reached[sum] as usize;
+        let deleted = (0..position)
+            .rev()
+            .find(|&candidate| {
+                directories[candidate].1 == position
+                    && size(candidate) <= sum
+                    && first_reached[sum - size(candidate)] as usize <= candidate
+            })
+            .unwrap();
+        plan.push(directories[deleted].0);
+        sum -= size(deleted);
+    }
+    plan.reverse();
+
+    let deleted_bytes = plan
+        .iter()
+        .map(|&directory| file_system.objects[directory].size)
+        .sum();
+    if scale > 1 && deleted_bytes >= single.deleted_bytes {
+        return Ok(DeletionPlan {
+            optimal: false,
+            ..single
+        });
+    }
+
+    Ok(DeletionPlan {
+        directories: plan,
+        deleted_bytes,
+        optimal: scale == 1,
+    })
+}
+
+// Most sets of sums waiting at once for the end of a subtree
+fn max_pending(directories: &[(usize, usize)]) -> usize {
+    let mut pending = BTreeSet::new();
+    let mut max_pending = 0;
+    for (position, &(_, end)) in directories.iter().enumerate() {
+        pending.insert(end);
+        max_pending = max_pending.max(pending.len());
+        pending.remove(&(position + 1));
+    }
+    max_pending
+}
+
+// `destination |= source << shift`, dropping bits at or past `width`
+fn or_shifted(destination: &mut [u64], source: &[u64], shift: usize, width: usize) {
+    let (word_shift, bit_shift) = (shift / 64, shift % 64);
+    for index in (word_shift..destination.len()).rev() {
+        let mut shifted = source[index - word_shift] << bit_shift;
+        if bit_shift > 0 && index > word_shift {
+            shifted |= source[index - word_shift - 1] >> (64 - bit_shift);
+        }
+        destination[index] |= shifted;
+    }
+
+    if !width.is_multiple_of(64) {
+        *destination.last_mut().unwrap() &= (1 << (width % 64)) - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::FileSystemObject;
+
+    // A small random tree: every object goes into a random directory created before it
+    fn random_file_system(seed: u64) -> FileSystem {
+        let mut state = seed.max(1);
+        let mut next = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize % bound
+        };
+
+        let mut file_system = FileSystem::new();
+        let mut directories = vec![file_system.root];
+        for index in 0..1 + next(20) {
+            let parent = directories[next(directories.len())];
+            let name = format!("o{index}");
+            let object = if next(5) < 2 {
+                FileSystemObject::new_directory(name, parent)
+            } else {
+                FileSystemObject::new_file(name, 1 + next(100), parent)
+            };
+            let is_directory = object.is_directory();
+            let object = file_system.objects.insert(object);
+            file_system.objects[parent]
+                .children
+                .as_mut()
+                .unwrap()
+                .push(object);
+            if is_directory {
+                directories.push(object);
+            }
+        }
+
+        file_system.resolve_sizes();
+        file_system
+    }
+
+    // Every set of directories in the subtree of `directory` with none inside another
+    fn all_plans(file_system: &FileSystem, directory: usize) -> Vec<Vec<usize>> {
+        let mut plans = vec![Vec::new()];
+        for &child in file_system.objects[directory].children.iter().flatten() {
+            if !file_system.objects[child].is_directory() {
+                continue;
+            }
+            let child_plans = all_plans(file_system, child);
+            plans = plans
+                .iter()
+                .flat_map(|plan| {
+                    child_plans
+                        .iter()
+                        .map(move |child_plan| [plan.clone(), child_plan.clone()].concat())
+                })
+                .collect();
+        }
+        plans.push(vec![directory]);
+        plans
+    }
+
+    fn size_of(file_system: &FileSystem, plan: &[usize]) -> usize {
+        plan.iter()
+            .map(|&directory| file_system.objects[directory].size)
+            .sum()
+    }
+
+    fn assert_valid(file_system: &FileSystem, plan: &DeletionPlan, target: usize) {
+        assert_eq!(size_of(file_system, &plan.directories), plan.deleted_bytes);
+        assert!(plan.deleted_bytes >= target);
+
+        for &directory in &plan.directories {
+            let mut ancestor = file_system.objects[directory].parent;
+            while let Some(current) = ancestor {
+                assert!(!plan.directories.contains(&current));
+                ancestor = file_system.objects[current].parent;
+            }
+        }
+    }
+
+    #[test]
+    fn fewest_bytes_matches_brute_force() {
+        for seed in 1..300 {
+            let file_system = random_file_system(seed);
+            let plans = all_plans(&file_system, file_system.root);
+            let root_size = file_system.objects[file_system.root].size;
+
+            for target in [1, root_size / 3, root_size / 2, root_size, root_size + 1] {
+                let best = plans
+                    .iter()
+                    .map(|plan| size_of(&file_system, plan))
+                    .filter(|&size| size >= target)
+                    .min();
+
+                let plan = plan_deletions(&file_system, target, Objective::DeletedBytes).unwrap();
+                assert_eq!(plan.as_ref().map(|plan| plan.deleted_bytes), best);
+                if let Some(plan) = plan {
+                    assert_valid(&file_system, &plan, target);
+                    assert!(plan.optimal);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fewest_deletions_is_the_smallest_large_enough_directory() {
+        for seed in 1..100 {
+            let file_system = random_file_system(seed);
+            let target = file_system.objects[file_system.root].size / 2 + 1;
+
+            let Some(plan) = plan_deletions(&file_system, target, Objective::Deletions).unwrap()
+            else {
+                continue;
+            };
+            assert_valid(&file_system, &plan, target);
+            assert_eq!(plan.directories.len(), 1);
+
+            let smallest = file_system
+                .objects
+                .iter()
+                .filter(|(_, object)| object.is_directory() && object.size >= target)
+                .map(|(_, object)| object.size)
+                .min();
+            assert_eq!(Some(plan.deleted_bytes), smallest);
+        }
+    }
+
+    #[test]
+    fn scaled_search_still_frees_enough() {
+        for seed in 1..300 {
+            let file_system = random_file_system(seed);
+            let target = file_system.objects[file_system.root].size / 3 + 1;
+
+            // Without files, nothing frees a single byte
+            let Some(exact) =
+                plan_deletions(&file_system, target, Objective::DeletedBytes).unwrap()
+            else {
+                continue;
+            };
+            let single = plan_deletions(&file_system, target, Objective::Deletions)
+                .unwrap()
+                .unwrap();
+            let scaled = plan_deletions_within(&file_system, target, Objective::DeletedBytes, 64)
+                .unwrap()
+                .unwrap();
+
+            assert_valid(&file_system, &scaled, target);
+            assert!(scaled.deleted_bytes >= exact.deleted_bytes);
+            assert!(scaled.deleted_bytes <= single.deleted_bytes);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail};
+use slab::Slab;
+
+pub struct FileSystemObject {
+    pub name: String,
+    pub size: usize,
+    pub children: Option<Vec<usize>>, // Only present for directories. Items are children's IDs.
+    pub parent: Option<usize>,        // Only present for non-root objects
+}
+
+impl FileSystemObject {
+    pub fn new_file(name: String, size: usize, parent: usize) -> Self {
+        Self {
+            name,
+            size,
+            children: None,
+            parent: Some(parent),
+        }
+    }
+
+    pub fn new_directory(name: String, parent: usize) -> Self {
+        Self {
+            name,
+            size: 0,
+            children: Some(Vec::new()),
+            parent: Some(parent),
+        }
+    }
+
+    pub fn new_root() -> Self {
+        Self {
+            name: String::from("/"),
+            size: 0,
+            children: Some(Vec::new()),
+            parent: None,
+        }
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.children.is_some()
+    }
+}
+
+pub struct FileSystem {
+    pub objects: Slab<FileSystemObject>,
+    pub root: usize,
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        let mut objects = Slab::new();
+        let root = objects.insert(FileSystemObject::new_root());
+        Self { objects, root }
+    }
+
+    // Rebuilds the tree described by a terminal transcript. Directories are created from `ls`
+    // output and reused when revisited; listing a directory again must agree with the first
+    // listing, entry for entry. Directory sizes are left at 0 until `resolve_sizes` is called.
+    pub fn from_transcript(transcript: &str) -> anyhow::Result<Self> {
+        let mut file_system = Self::new();
+        let mut current_directory = file_system.root;
+        let mut listed_directories = HashSet::new();
+        // Directory whose `ls` output is being read and, if it was listed before, the children
+        // listed again so far
+        let mut listing: Option<(usize, Option<HashSet<usize>>)> = None;
+
+        for (line_index, line) in transcript.lines().enumerate() {
+            let line_number = line_index + 1;
+            let words = line.split_whitespace().collect::<Vec<_>>();
+
+            // Any command ends the `ls` output being read
+            let ended_listing = match words.first() {
+                Some(&"$") => file_system.end_listing(listing.take()),
+                _ => Ok(()),
+            };
+
+            let result = ended_listing.and_then(|()| match words[..] {
+                [] => Ok(()),
+                ["$", "cd", "/"] => {
+                    current_directory = file_system.root;
+                    Ok(())
+                }
+                ["$", "cd", ".."] => match file_system.objects[current_directory].parent {
+                    Some(parent) => {
+                        current_directory = parent;
+                        Ok(())
+                    }
+                    None => Err(anyhow!("Cannot `cd ..` from root directory")),
+                },
+                ["$", "cd", dir] => match file_system.child(current_directory, dir) {
+                    Some(child) if file_system.objects[child].is_directory() => {
+                        current_directory = child;
+                        Ok(())
+                    }
+                    Some(_) => Err(anyhow!("Cannot `cd` into {dir:?}, it is a file")),
+                    None => Err(anyhow!(
+                        "Cannot `cd` into {dir:?}, no such directory in {}",
+                        file_system.path(current_directory)
+                    )),
+                },
+                ["$", "ls"] => {
+                    let listed_before = !listed_directories.insert(current_directory);
+                    listing = Some((current_directory, listed_before.then(HashSet::new)));
+                    Ok(())
+                }
+                ["$", ..] => Err(anyhow!("Unknown command")),
+                [entry, name] => match &mut listing {
+                    None => Err(anyhow!("Listing entry outside of `ls` output")),
+                    Some((directory, relisted)) => file_system
+                        .add_listing_entry(*directory, entry, name, relisted.is_some())
+                        .map(|child| {
+                            if let Some(relisted) = relisted {
+                                relisted.insert(child);
+                            }
+                        }),
+                },
+                _ => Err(anyhow!("Invalid input line")),
+            });
+
+            result.map_err(|error| anyhow!("Line {line_number}: {error}: {line:?}"))?;
+        }
+
+        file_system
+            .end_listing(listing)
+            .map_err(|error| anyhow!("End of input: {error}"))?;
+
+        Ok(file_system)
+    }
+
+    // A directory listed again must list every child it had
+    fn end_listing(&self, listing: Option<(usize, Option<HashSet<usize>>)>) -> anyhow::Result<()> {
+        let Some((directory, Some(relisted))) = listing else {
+            return Ok(());
+        };
+
+        let children = self.objects[directory].children.iter().flatten();
+        match children.copied().find(|child| !relisted.contains(child)) {
+            Some(missing) => bail!(
+                "{} was listed again without {:?}",
+                self.path(directory),
+                self.objects[missing].name
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn add_listing_entry(
+        &mut self,
+        directory: usize,
+        entry: &str,
+        name: &str,
+        listed_before: bool,
+    ) -> anyhow::Result<usize> {
+        if !is_valid_name(name) {
+            bail!("Invalid name {name:?}");
+        }
+
+        let size = match entry {
+            "dir" => None,
+            size => Some(
+                size.parse::<usize>()
+                    .map_err(|_| anyhow!("Could not parse size"))?,
+            ),
+        };
+
+        if let Some(child) = self.child(directory, name) {
+            let existing = &self.objects[child];
+            let matches = match size {
+                None => existing.is_directory(),
+                Some(size) => !existing.is_directory() && existing.size == size,
+            };
+
+            if !listed_before {
+                bail!(
+                    "{name:?} appears twice in the listing of {}",
+                    self.path(directory)
+                );
+            }
+            if !matches {
+                bail!(
+                    "{} is listed again with a different entry for {name:?}",
+                    self.path(directory)
+                );
+            }
+            return Ok(child);
+        }
+
+        if listed_before {
+            bail!(
+                "{} is listed again with a new entry {name:?}",
+                self.path(directory)
+            );
+        }
+
+        let new_object = match size {
+            None => FileSystemObject::new_directory(name.to_string(), directory),
+            Some(size) => FileSystemObject::new_file(name.to_string(), size, directory),
+        };
+        let new_object = self.objects.insert(new_object);
+
+        // Add new object to the directory's children
+        self.objects[directory]
+            .children
+            .as_mut()
+            .unwrap()
+            .push(new_object);
+
+        Ok(new_object)
+    }
+
+    // Child of `directory` with the given name, if any
+    pub fn child(&self, directory: usize, name: &str) -> Option<usize> {
+        self.objects[directory]
+            .children
+            .as_ref()?
+            .iter()
+            .copied()
+            .find(|&child| self.objects[child].name == name)
+    }
+
+    // Absolute path of an object, e.g. `/a/e`
+    pub fn path(&self, object: usize) -> String {
+        let mut names = Vec::new();
+        let mut current = object;
+        while let Some(parent) = self.objects[current].parent {
+            names.push(self.objects[current].name.as_str());
+            current = parent;
+        }
+
+        if names.is_empty() {
+            return String::from("/");
+        }
+
+        names.iter().rev().map(|name| format!("/{name}")).collect()
+    }
+
+    // Sets each directory's size to the total size of its contents. Children are resolved before
+    // their parent by going through a pre-order listing backwards, so deep trees don't recurse.
+    pub fn resolve_sizes(&mut self) {
+        let mut pre_order = Vec::with_capacity(self.objects.len());
+        let mut stack = vec![self.root];
+        while let Some(object) = stack.pop() {
+            pre_order.push(object);
+            stack.extend(self.objects[object].children.iter().flatten());
+        }
+
+        for &object in pre_order.iter().rev() {
+            if let Some(children) = &self.objects[object].children {
+                let size = children.iter().map(|&child| self.objects[child].size).sum();
+                self.objects[object].size = size;
+            }
+        }
+    }
+}
+
+// Names must stay inside their directory once turned into paths
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains('/')
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        json::{from_json, to_json},
+        query::{Query, QueryResult},
+        render::{render_du, RenderOptions},
+    };
+
+    const DEPTH: usize = 50_000;
+
+    // A chain of `DEPTH` nested directories `d`, each holding a one-byte file `f`
+    fn deep_file_system() -> FileSystem {
+        let mut transcript = String::from("$ cd /\n");
+        for _ in 0..DEPTH {
+            transcript.push_str("$ ls\ndir d\n1 f\n$ cd d\n");
+        }
+        transcript.push_str("$ ls\n1 f\n");
+
+        let mut file_system = FileSystem::from_transcript(&transcript).unwrap();
+        file_system.resolve_sizes();
+        file_system
+    }
+
+    fn value(query: &str, file_system: &FileSystem) -> QueryResult {
+        query.parse::<Query>().unwrap().run(file_system)
+    }
+
+    #[test]
+    fn relisting_must_cover_every_child() {
+        let listing = "$ cd /\n$ ls\ndir a\n10 b\n";
+
+        let complete = format!("{listing}$ ls\n10 b\ndir a\n$ cd a\n");
+        assert!(FileSystem::from_transcript(&complete).is_ok());
+
+        let error = FileSystem::from_transcript(&format!("{listing}$ ls\n10 b\n$ cd a\n"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 7: / was listed again without \"a\": \"$ cd a\""
+        );
+
+        let error = FileSystem::from_transcript(&format!("{listing}$ ls\ndir a\n"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "End of input: / was listed again without \"b\""
+        );
+    }
+
+    #[test]
+    fn deep_tree_sizes_resolve() {
+        let file_system = deep_file_system();
+
+        // The directory at depth `d` holds the files of every level from `d` down
+        assert_eq!(file_system.objects[file_system.root].size, DEPTH + 1);
+        let mut directory = file_system.root;
+        for depth in 0..=DEPTH {
+            assert_eq!(file_system.objects[directory].size, DEPTH + 1 - depth);
+            if let Some(child) = file_system.child(directory, "d") {
+                directory = child;
+            }
+        }
+
+        // Resolving again must not count anything twice
+        let mut file_system = file_system;
+        file_system.resolve_sizes();
+        assert_eq!(file_system.objects[file_system.root].size, DEPTH + 1);
+    }
+
+    #[test]
+    fn deep_tree_renders_and_queries() {
+        let file_system = deep_file_system();
+
+        let options = RenderOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_du(&file_system, &options),
+            format!("{DEPTH}\t/d\n{}\t/\n", DEPTH + 1)
+        );
+
+        assert_eq!(
+            value("dirs | count", &file_system),
+            QueryResult::Value(Some(DEPTH + 1))
+        );
+        assert_eq!(
+            value("files where depth > 100 | sum size", &file_system),
+            QueryResult::Value(Some(DEPTH + 1 - 100))
+        );
+        assert_eq!(
+            value("dirs | max depth", &file_system),
+            QueryResult::Value(Some(DEPTH))
+        );
+    }
+
+    #[test]
+    fn deep_tree_json_round_trip() {
+        let file_system = deep_file_system();
+        let json = to_json(&file_system).unwrap();
+        let imported = from_json(&json).unwrap();
+
+        assert_eq!(imported.objects.len(), file_system.objects.len());
+        assert_eq!(imported.objects[imported.root].size, DEPTH + 1);
+        assert_eq!(to_json(&imported).unwrap(), json);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use crate::file_system::{is_valid_name, FileSystem, FileSystemObject};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    File,
+    Directory,
+}
+
+// One object of a nested JSON snapshot, e.g.
+//
+//     {"name": "/", "kind": "directory", "size": 584, "children": [
+//         {"name": "i", "kind": "file", "size": 584}
+//     ]}
+//
+// Snapshots can be as deep as file systems, so nodes are built and dropped without recursion, and
+// (de)serialized on a stack that grows as needed. The JSON is compact, as indenting it would take
+// space quadratic in the depth.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Node {
+    pub name: String,
+    pub kind: Kind,
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn from_file_system(file_system: &FileSystem) -> Self {
+        // Post-order traversal: a node is built once all of its children have been, from the last
+        // nodes in `built`
+        let mut built: Vec<Node> = Vec::new();
+        let mut stack = vec![(file_system.root, false)];
+
+        while let Some((object, children_visited)) = stack.pop() {
+            let file_system_object = &file_system.objects[object];
+
+            if let (Some(children), false) = (&file_system_object.children, children_visited) {
+                stack.push((object, true));
+                stack.extend(children.iter().rev().map(|&child| (child, false)));
+                continue;
+            }
+
+            let child_count = file_system_object.children.as_ref().map_or(0, Vec::len);
+            let children = built.split_off(built.len() - child_count);
+            built.push(Node {
+                name: file_system_object.name.clone(),
+                kind: if file_system_object.is_directory() {
+                    Kind::Directory
+                } else {
+                    Kind::File
+                },
+                size: file_system_object.size,
+                children,
+            });
+        }
+
+        built.pop().unwrap()
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        // Descendants are dropped once emptied, so dropping one never recurses
+        let mut descendants = std::mem::take(&mut self.children);
+        while let Some(mut descendant) = descendants.pop() {
+            descendants.append(&mut descendant.children);
+        }
+    }
+}
+
+// Sizes must be resolved
+pub fn to_json(file_system: &FileSystem) -> serde_json::Result<String> {
+    let mut json = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut json);
+    Node::from_file_system(file_system)
+        .serialize(serde_stacker::Serializer::new(&mut serializer))?;
+
+    Ok(String::from_utf8(json).unwrap())
+}
+
+// Rebuilds the file system from a snapshot. The root must be a directory, and directory sizes must
+// add up to what they contain.
+pub fn from_json(json: &str) -> anyhow::Result<FileSystem> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    deserializer.disable_recursion_limit();
+    let root = Node::deserialize(serde_stacker::Deserializer::new(&mut deserializer))?;
+    deserializer.end()?;
+    if root.kind != Kind::Directory {
+        bail!("The root must be a directory");
+    }
+
+    let mut file_system = FileSystem::new();
+    let mut stack = vec![(&root, file_system.root)];
+
+    while let Some((node, directory)) = stack.pop() {
+        let mut names = HashSet::new();
+
+        for child in &node.children {
+            if !is_valid_name(&child.name) {
+                bail!(
+                    "Invalid name {:?} in {}",
+                    child.name,
+                    file_system.path(directory)
+                );
+            }
+            if !names.insert(child.name.as_str()) {
+                bail!(
+                    "{:?} appears twice in {}",
+                    child.name,
+                    file_system.path(directory)
+                );
+            }
+
+            let object = match child.kind {
+                Kind::Directory => FileSystemObject::new_directory(child.name.clone(), directory),
+                Kind::File if child.children.is_empty() => {
+                    FileSystemObject::new_file(child.name.clone(), child.size, directory)
+                }
+                Kind::File => bail!(
+                    "File {}/{} has children",
+                    file_system.path(directory).trim_end_matches('/'),
+                    child.name
+                ),
+            };
+            let object = file_system.objects.insert(object);
+            file_system.objects[directory]
+                .children
+                .as_mut()
+                .unwrap()
+                .push(object);
+
+            if child.kind == Kind::Directory {
+                stack.push((child, object));
+            }
+        }
+    }
+
+    file_system.resolve_sizes();
+
+    // Compare stated directory sizes with the resolved ones, visiting both trees in step
+    let mut stack = vec![(&root, file_system.root)];
+    while let Some((node, object)) = stack.pop() {
+        let size = file_system.objects[object].size;
+        if node.size != size {
+            bail!(
+                "{} has size {} but its contents add up to {size}",
+                file_system.path(object),
+                node.size
+            );
+        }
+
+        let children = file_system.objects[object].children.iter().flatten();
+        stack.extend(node.children.iter().zip(children.copied()));
+    }
+
+    Ok(file_system)
+}
@@ -0,0 +1,6 @@
+pub mod deletion;
+pub mod file_system;
+pub mod json;
+pub mod query;
+pub mod render;
+pub mod transcript;
@@ -1,154 +1,154 @@
-use slab::Slab;
+use std::path::Path;
 
-struct FileSystemObject {
-    name: String, // Debugging purposes only
-    size: usize,
-    children: Option<Vec<usize>>, // Only present for directories. Items are children's IDs.
-    parent: Option<usize>,        // Only present for non-root objects
-}
+use day_07::{
+    deletion::{bytes_to_free, plan_deletions},
+    file_system::FileSystem,
+    json::{from_json, to_json},
+    query::{Query, QueryResult},
+    render::{render_du, render_tree, RenderOptions},
+    transcript::{materialize, transcript_from_directory},
+};
 
-impl FileSystemObject {
-    fn new_file(name: String, size: usize, parent: usize) -> Self {
-        Self {
-            name,
-            size,
-            children: None,
-            parent: Some(parent),
-        }
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    // Print the transcript of exploring a real directory instead of solving the puzzle
+    if let Some(directory) = option_value("--transcript-from") {
+        print!("{}", transcript_from_directory(Path::new(directory))?);
+        return Ok(());
     }
 
-    fn new_directory(name: String, parent: usize) -> Self {
-        Self {
-            name,
-            size: 0,
-            children: Some(Vec::new()),
-            parent: Some(parent),
+    // A JSON snapshot can stand in for the transcript
+    let file_system = match option_value("--import-json") {
+        Some(path) => from_json(&std::fs::read_to_string(path)?)?,
+        None => {
+            let input = match option_value("--input") {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => include_str!("../input.txt").to_string(),
+            };
+            let mut file_system = FileSystem::from_transcript(&input)?;
+
+            // Resolve directory sizes
+            file_system.resolve_sizes();
+            file_system
         }
+    };
+
+    // Recreate the file system as sparse files in a new temporary directory
+    if flag("--materialize") {
+        let destination = std::env::temp_dir().join(format!("day_07-{}", std::process::id()));
+        materialize(&file_system, &destination)?;
+        println!("{}", destination.display());
+        return Ok(());
     }
-}
 
-fn main() -> anyhow::Result<()> {
-    let input = include_str!("../input.txt");
-    let mut input_lines = input
-        .lines()
-        .map(|line| line.split_whitespace().collect::<Vec<_>>());
-
-    let mut file_system_objects = Slab::new();
-
-    // Create root directory and skip first line
-    let _ = input_lines.next();
-    let root_directory = file_system_objects.insert(FileSystemObject {
-        name: String::from("/"),
-        size: 0,
-        children: Some(Vec::new()),
-        parent: None,
-    });
-
-    let mut current_directory = root_directory;
-
-    for line in input_lines {
-        match line[..] {
-            ["$", "cd", ".."] => {
-                current_directory = file_system_objects[current_directory]
-                    .parent
-                    .expect("Cannot `cd ..` from root directory");
-            }
-            ["$", "cd", dir] => {
-                let new_directory = file_system_objects.insert(FileSystemObject::new_directory(
-                    dir.to_string(),
-                    current_directory,
-                ));
-
-                // Add new directory to current directory's children
-                file_system_objects[current_directory]
-                    .children
-                    .as_mut()
-                    .unwrap()
-                    .push(new_directory);
-
-                current_directory = new_directory;
-            }
-            ["$", "ls"] => {}
-            ["dir", _dir_name] => {}
-            [size, name] => {
-                let size = size.parse::<usize>().expect("Could not parse size");
-
-                // Create new file
-                let new_file = file_system_objects.insert(FileSystemObject::new_file(
-                    name.to_string(),
-                    size,
-                    current_directory,
-                ));
-
-                // Add new file to current directory's children
-                file_system_objects[current_directory]
-                    .children
-                    .as_mut()
-                    .unwrap()
-                    .push(new_file);
-            }
-            _ => unreachable!("Invalid input line: {:?}", line),
-        }
+    if flag("--export-json") {
+        println!("{}", to_json(&file_system)?);
+        return Ok(());
     }
 
-    // Resolve directory sizes
-    resolve_file_system_object_size(root_directory, &mut file_system_objects);
-
-    // Part 1
-    // Find all directories with size <= 100_000
-    let mut part_1 = 0;
-    let mut file_system_object_stack = vec![root_directory];
-    while let Some(file_system_object) = file_system_object_stack.pop() {
-        let Some(children) = &file_system_objects[file_system_object].children else {
-            // Not a directory, skip
-            continue;
+    // Print the reconstructed file system instead of solving the puzzle
+    if flag("--tree") || flag("--du") {
+        let options = RenderOptions {
+            max_depth: option_value("--max-depth")
+                .map(|max_depth| max_depth.parse())
+                .transpose()?,
+            sort_by_size: flag("--sort-size"),
+            human_readable: flag("--human"),
+            include_files: flag("--all"),
         };
 
-        file_system_object_stack.extend(children);
+        if flag("--tree") {
+            print!("{}", render_tree(&file_system, &options));
+        } else {
+            print!("{}", render_du(&file_system, &options));
+        }
 
-        let size = file_system_objects[file_system_object].size;
+        return Ok(());
+    }
 
-        if size <= 100_000 {
-            part_1 += size;
+    if let Some(query) = option_value("--query") {
+        match query.parse::<Query>()?.run(&file_system) {
+            QueryResult::Rows(rows) => {
+                for (object, _) in rows {
+                    let size = file_system.objects[object].size;
+                    println!("{size}\t{}", file_system.path(object));
+                }
+            }
+            QueryResult::Value(Some(value)) => println!("{value}"),
+            QueryResult::Value(None) => println!("No match"),
         }
+
+        return Ok(());
     }
 
+    // Part 1
+    // Find all directories with size <= 100_000
+    let part_1 = value_query("dirs where size <= 100000 | sum size", &file_system)?;
+
     println!("Part 1: {part_1}");
 
     // Part 2
     // Find the smallest directory to delete to get 30_000_000 unused space assuming 70_000_000 total space
-    let size_to_delete = 30_000_000 - (70_000_000 - file_system_objects[root_directory].size);
+    let disk_size = match option_value("--disk-size") {
+        Some(disk_size) => disk_size.parse()?,
+        None => 70_000_000,
+    };
+    let required_free = match option_value("--required-free") {
+        Some(required_free) => required_free.parse()?,
+        None => 30_000_000,
+    };
+    let size_to_delete = bytes_to_free(&file_system, disk_size, required_free)?;
+
+    let part_2 = if size_to_delete == 0 {
+        0
+    } else {
+        value_query(
+            &format!("dirs where size >= {size_to_delete} | min size"),
+            &file_system,
+        )?
+    };
 
-    let mut part_2 = usize::MAX;
-    let mut file_system_object_stack = vec![root_directory];
+    println!("Part 2: {part_2}");
 
-    while let Some(file_system_object) = file_system_object_stack.pop() {
-        let Some(children) = &file_system_objects[file_system_object].children else {
-            // Not a directory, skip
-            continue;
+    // Several directories may free enough space with fewer bytes than the single best one
+    if let Some(objective) = option_value("--plan") {
+        let objective = objective.parse()?;
+        let Some(plan) = plan_deletions(&file_system, size_to_delete, objective)? else {
+            anyhow::bail!("Deleting every directory doesn't free {size_to_delete} bytes");
         };
 
-        file_system_object_stack.extend(children);
-
-        let size = file_system_objects[file_system_object].size;
-
-        if size >= size_to_delete && size < part_2 {
-            part_2 = size;
+        println!(
+            "Delete {} director{} to free {} bytes (needed {size_to_delete}):",
+            plan.directories.len(),
+            if plan.directories.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            plan.deleted_bytes
+        );
+        if !plan.optimal {
+            println!("(sizes too large to search exactly, a better plan may exist)");
+        }
+        for directory in plan.directories {
+            let size = file_system.objects[directory].size;
+            println!("{size}\t{}", file_system.path(directory));
         }
     }
 
-    println!("Part 2: {part_2}");
-
     Ok(())
 }
 
-fn resolve_file_system_object_size(root: usize, file_system_objects: &mut Slab<FileSystemObject>) {
-    if let Some(children) = file_system_objects[root].children.clone()
-    /* Make borrow checker happy */
-    {
-        for child in children {
-            resolve_file_system_object_size(child, file_system_objects);
-            file_system_objects[root].size += file_system_objects[child].size;
-        }
+fn value_query(query: &str, file_system: &FileSystem) -> anyhow::Result<usize> {
+    match query.parse::<Query>()?.run(file_system) {
+        QueryResult::Value(Some(value)) => Ok(value),
+        _ => Err(anyhow::anyhow!("No result for query {query:?}")),
     }
 }
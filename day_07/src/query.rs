@@ -0,0 +1,337 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use anyhow::{anyhow, bail};
+
+use crate::file_system::FileSystem;
+
+// A small query language over the file system, e.g.
+//
+//     dirs where size <= 100000 | sum size
+//     files where name ~ *.txt and depth > 1 | sort size desc | limit 3
+//
+// A query selects `all`, `dirs` or `files`, optionally filters them on `size`, `depth` (the root is
+// at depth 0) or `name`, then goes through `|`-separated stages: `sort <field> [asc|desc]`,
+// `limit <n>`, and at most one final aggregation among `count`, `sum <field>`, `min <field>` and
+// `max <field>`. Numeric fields compare with `<`, `<=`, `>`, `>=`, `=` and `!=`; names compare with
+// `=`, `!=` and `~` (glob with `*` and `?`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    kind: Kind,
+    conditions: Vec<Condition>,
+    stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    All,
+    Directories,
+    Files,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumericField {
+    Size,
+    Depth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Numeric(NumericField, Comparison, usize),
+    Name {
+        pattern: String,
+        glob: bool,
+        negated: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Sort(NumericField, bool), // Descending if true
+    Limit(usize),
+    Count,
+    Sum(NumericField),
+    Min(NumericField),
+    Max(NumericField),
+}
+
+impl Stage {
+    fn is_aggregation(&self) -> bool {
+        matches!(
+            self,
+            Stage::Count | Stage::Sum(_) | Stage::Min(_) | Stage::Max(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryResult {
+    // Matching objects with their depth, in order
+    Rows(Vec<(usize, usize)>),
+    // `None` for the min or max of nothing
+    Value(Option<usize>),
+}
+
+impl FromStr for NumericField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "size" => Ok(NumericField::Size),
+            "depth" => Ok(NumericField::Depth),
+            _ => Err(anyhow!("Unknown field {s:?}, expected size or depth")),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Greater => ordering.is_gt(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::NotEqual => ordering.is_ne(),
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<" => Ok(Comparison::Less),
+            "<=" => Ok(Comparison::LessOrEqual),
+            ">" => Ok(Comparison::Greater),
+            ">=" => Ok(Comparison::GreaterOrEqual),
+            "=" => Ok(Comparison::Equal),
+            "!=" => Ok(Comparison::NotEqual),
+            _ => Err(anyhow!("Unknown comparison {s:?}")),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [field, operator, value] = s.split_whitespace().collect::<Vec<_>>()[..] else {
+            bail!("Expected a condition like `size <= 100000`, got {s:?}");
+        };
+
+        if field == "name" {
+            let (glob, negated) = match operator {
+                "=" => (false, false),
+                "!=" => (false, true),
+                "~" => (true, false),
+                _ => bail!("Names can only be compared with =, != or ~, got {operator:?}"),
+            };
+            return Ok(Condition::Name {
+                pattern: value.to_string(),
+                glob,
+                negated,
+            });
+        }
+
+        Ok(Condition::Numeric(
+            field.parse()?,
+            operator.parse()?,
+            value
+                .parse()
+                .map_err(|_| anyhow!("Expected a number, got {value:?}"))?,
+        ))
+    }
+}
+
+impl FromStr for Stage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            ["sort", field] | ["sort", field, "asc"] => Ok(Stage::Sort(field.parse()?, false)),
+            ["sort", field, "desc"] => Ok(Stage::Sort(field.parse()?, true)),
+            ["limit", count] => {
+                Ok(Stage::Limit(count.parse().map_err(|_| {
+                    anyhow!("Expected a number, got {count:?}")
+                })?))
+            }
+            ["count"] => Ok(Stage::Count),
+            ["sum", field] => Ok(Stage::Sum(field.parse()?)),
+            ["min", field] => Ok(Stage::Min(field.parse()?)),
+            ["max", field] => Ok(Stage::Max(field.parse()?)),
+            _ => Err(anyhow!("Unknown stage {s:?}")),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('|');
+        let selection = parts.next().unwrap().trim();
+
+        let (kind, conditions) = match selection.split_once(char::is_whitespace) {
+            Some((kind, rest)) => {
+                let Some(conditions) = rest.trim().strip_prefix("where") else {
+                    bail!("Expected `where` after {kind:?}, got {:?}", rest.trim());
+                };
+                (kind, Some(conditions))
+            }
+            None => (selection, None),
+        };
+
+        let kind = match kind {
+            "all" => Kind::All,
+            "dirs" => Kind::Directories,
+            "files" => Kind::Files,
+            _ => bail!("Expected all, dirs or files, got {kind:?}"),
+        };
+
+        let conditions = match conditions {
+            Some(conditions) => conditions
+                .split(" and ")
+                .map(Condition::from_str)
+                .collect::<anyhow::Result<_>>()?,
+            None => Vec::new(),
+        };
+
+        let stages: Vec<Stage> = parts.map(Stage::from_str).collect::<anyhow::Result<_>>()?;
+
+        if let Some(position) = stages.iter().position(Stage::is_aggregation) {
+            if position != stages.len() - 1 {
+                bail!("An aggregation must be the last stage of a query");
+            }
+        }
+
+        Ok(Query {
+            kind,
+            conditions,
+            stages,
+        })
+    }
+}
+
+impl Query {
+    pub fn run(&self, file_system: &FileSystem) -> QueryResult {
+        let numeric_value = |field: NumericField, (object, depth): (usize, usize)| match field {
+            NumericField::Size => file_system.objects[object].size,
+            NumericField::Depth => depth,
+        };
+
+        // Every object with its depth, in pre-order
+        let mut rows = Vec::new();
+        let mut stack = vec![(file_system.root, 0)];
+        while let Some((object, depth)) = stack.pop() {
+            let file_system_object = &file_system.objects[object];
+
+            let kind_matches = match self.kind {
+                Kind::All => true,
+                Kind::Directories => file_system_object.is_directory(),
+                Kind::Files => !file_system_object.is_directory(),
+            };
+
+            let conditions_match = self.conditions.iter().all(|condition| match condition {
+                Condition::Numeric(field, comparison, value) => {
+                    comparison.holds(numeric_value(*field, (object, depth)).cmp(value))
+                }
+                Condition::Name {
+                    pattern,
+                    glob,
+                    negated,
+                } => {
+                    let name = &file_system_object.name;
+                    let matches = if *glob {
+                        glob_match(pattern, name)
+                    } else {
+                        pattern == name
+                    };
+                    matches != *negated
+                }
+            });
+
+            if kind_matches && conditions_match {
+                rows.push((object, depth));
+            }
+
+            if let Some(children) = &file_system_object.children {
+                stack.extend(children.iter().rev().map(|&child| (child, depth + 1)));
+            }
+        }
+
+        for stage in &self.stages {
+            match *stage {
+                Stage::Sort(field, descending) => {
+                    rows.sort_by_key(|&row| numeric_value(field, row));
+                    if descending {
+                        rows.reverse();
+                    }
+                }
+                Stage::Limit(count) => rows.truncate(count),
+                Stage::Count => return QueryResult::Value(Some(rows.len())),
+                Stage::Sum(field) => {
+                    return QueryResult::Value(Some(
+                        rows.iter().map(|&row| numeric_value(field, row)).sum(),
+                    ))
+                }
+                Stage::Min(field) => {
+                    return QueryResult::Value(
+                        rows.iter().map(|&row| numeric_value(field, row)).min(),
+                    )
+                }
+                Stage::Max(field) => {
+                    return QueryResult::Value(
+                        rows.iter().map(|&row| numeric_value(field, row)).max(),
+                    )
+                }
+            }
+        }
+
+        QueryResult::Rows(rows)
+    }
+}
+
+// `*` matches any run of characters and `?` any single character
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut pattern_index, mut name_index) = (0, 0);
+    // Position of the last `*` and of the name when it was reached, to backtrack to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, name_index));
+                pattern_index += 1;
+            }
+            Some(&c) if c == '?' || c == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star_index, star_name_index)) => {
+                    backtrack = Some((star_index, star_name_index + 1));
+                    pattern_index = star_index + 1;
+                    name_index = star_name_index + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|&c| c == '*')
+}
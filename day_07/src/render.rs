@@ -0,0 +1,156 @@
+use std::fmt::Write;
+
+use crate::file_system::FileSystem;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub max_depth: Option<usize>, // The root is at depth 0
+    pub sort_by_size: bool,       // Largest first, otherwise in listing order
+    pub human_readable: bool,     // `du -h` style sizes, e.g. `8.2M`
+    pub include_files: bool,      // Only used by `du`, like `du -a`
+}
+
+impl RenderOptions {
+    fn format_size(&self, size: usize) -> String {
+        if self.human_readable {
+            human_readable_size(size)
+        } else {
+            size.to_string()
+        }
+    }
+}
+
+// Rounds up like `du -h`: one decimal below 10, none above
+fn human_readable_size(size: usize) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64;
+    for unit in UNITS {
+        value /= 1024.0;
+
+        let rounded = if value < 10.0 {
+            (value * 10.0).ceil() / 10.0
+        } else {
+            value.ceil()
+        };
+
+        if rounded < 1024.0 || unit == UNITS[UNITS.len() - 1] {
+            return if rounded < 10.0 {
+                format!("{rounded:.1}{unit}")
+            } else {
+                format!("{rounded:.0}{unit}")
+            };
+        }
+    }
+
+    unreachable!()
+}
+
+fn sorted_children(file_system: &FileSystem, object: usize, options: &RenderOptions) -> Vec<usize> {
+    let mut children = file_system.objects[object]
+        .children
+        .clone()
+        .unwrap_or_default();
+    if options.sort_by_size {
+        children.sort_by_key(|&child| std::cmp::Reverse(file_system.objects[child].size));
+    }
+    children
+}
+
+// Renders the tree like `tree`, with the size of every object:
+//
+// / (48381165)
+// ├── a (94853)
+// │   └── e (584)
+// └── b.txt (14848514)
+pub fn render_tree(file_system: &FileSystem, options: &RenderOptions) -> String {
+    let mut output = String::new();
+    let root = &file_system.objects[file_system.root];
+    writeln!(output, "{} ({})", root.name, options.format_size(root.size)).unwrap();
+
+    // (object, depth, prefix for its own line, prefix for its children's lines)
+    let mut stack: Vec<(usize, usize, String, String)> = Vec::new();
+    let push_children = |stack: &mut Vec<_>, object: usize, depth: usize, prefix: &str| {
+        if options
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+        {
+            return;
+        }
+
+        let children = sorted_children(file_system, object, options);
+        // Pushed in reverse so they're popped in order
+        for (index, &child) in children.iter().enumerate().rev() {
+            let is_last = index == children.len() - 1;
+            let (branch, continuation) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            stack.push((
+                child,
+                depth + 1,
+                format!("{prefix}{branch}"),
+                format!("{prefix}{continuation}"),
+            ));
+        }
+    };
+
+    push_children(&mut stack, file_system.root, 0, "");
+
+    while let Some((object, depth, line_prefix, children_prefix)) = stack.pop() {
+        let file_system_object = &file_system.objects[object];
+        writeln!(
+            output,
+            "{line_prefix}{} ({})",
+            file_system_object.name,
+            options.format_size(file_system_object.size)
+        )
+        .unwrap();
+
+        push_children(&mut stack, object, depth, &children_prefix);
+    }
+
+    output
+}
+
+// Renders one `size<TAB>path` line per directory (and file, with `include_files`) like `du`:
+// children before their parent, or largest first with `sort_by_size`
+pub fn render_du(file_system: &FileSystem, options: &RenderOptions) -> String {
+    // Post-order traversal: an object is emitted once all of its children have been
+    let mut entries = Vec::new();
+    let mut stack = vec![(file_system.root, 0, false)];
+
+    while let Some((object, depth, children_visited)) = stack.pop() {
+        let file_system_object = &file_system.objects[object];
+
+        if children_visited || !file_system_object.is_directory() {
+            let shown = (options.include_files || file_system_object.is_directory())
+                && options.max_depth.is_none_or(|max_depth| depth <= max_depth);
+            if shown {
+                entries.push((file_system_object.size, file_system.path(object)));
+            }
+            continue;
+        }
+
+        stack.push((object, depth, true));
+        let children = file_system_object.children.as_ref().unwrap();
+        for &child in children.iter().rev() {
+            stack.push((child, depth + 1, false));
+        }
+    }
+
+    if options.sort_by_size {
+        entries.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    }
+
+    let mut output = String::new();
+    for (size, path) in entries {
+        writeln!(output, "{}\t{path}", options.format_size(size)).unwrap();
+    }
+    output
+}
@@ -0,0 +1,110 @@
+use std::{
+    fmt::Write,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::file_system::{is_valid_name, FileSystem};
+
+enum Step {
+    Enter(PathBuf),
+    Leave,
+}
+
+// Walks a real directory and writes the transcript of exploring it with `cd` and `ls`, listing
+// entries by name. Files are listed with their apparent size, as `du --apparent-size -b` counts
+// them; symlinks and special files are left out.
+pub fn transcript_from_directory(root: &Path) -> anyhow::Result<String> {
+    let mut transcript = String::new();
+    let mut stack = vec![Step::Enter(root.to_path_buf())];
+
+    while let Some(step) = stack.pop() {
+        let directory = match step {
+            Step::Enter(directory) => directory,
+            Step::Leave => {
+                writeln!(transcript, "$ cd ..").unwrap();
+                continue;
+            }
+        };
+
+        if directory == root {
+            writeln!(transcript, "$ cd /").unwrap();
+        } else {
+            writeln!(transcript, "$ cd {}", entry_name(&directory)?).unwrap();
+        }
+        writeln!(transcript, "$ ls").unwrap();
+
+        let mut entries = fs::read_dir(&directory)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("Could not list {}", directory.display()))?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut subdirectories = Vec::new();
+        for entry in entries {
+            let path = entry.path();
+            let name = entry_name(&path)?;
+            let metadata = fs::symlink_metadata(&path)
+                .with_context(|| format!("Could not read {}", path.display()))?;
+
+            if metadata.is_dir() {
+                writeln!(transcript, "dir {name}").unwrap();
+                subdirectories.push(path);
+            } else if metadata.is_file() {
+                writeln!(transcript, "{} {name}", metadata.len()).unwrap();
+            }
+        }
+
+        // Pushed in reverse so they're visited in order
+        for subdirectory in subdirectories.into_iter().rev() {
+            stack.push(Step::Leave);
+            stack.push(Step::Enter(subdirectory));
+        }
+    }
+
+    Ok(transcript)
+}
+
+// Names end up between spaces in the transcript, so they can't be empty or contain whitespace
+fn entry_name(path: &Path) -> anyhow::Result<&str> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("{} has no UTF-8 name", path.display()))?;
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        bail!("{name:?} can't be written in a transcript");
+    }
+
+    Ok(name)
+}
+
+// Recreates the file system under `destination`, which must not exist yet. Files are sparse: they
+// have their stated size but take next to no space on disk.
+pub fn materialize(file_system: &FileSystem, destination: &Path) -> anyhow::Result<()> {
+    let mut stack = vec![(file_system.root, destination.to_path_buf())];
+
+    while let Some((object, path)) = stack.pop() {
+        let file_system_object = &file_system.objects[object];
+
+        let Some(children) = &file_system_object.children else {
+            File::create(&path)
+                .and_then(|file| file.set_len(file_system_object.size as u64))
+                .with_context(|| format!("Could not create {}", path.display()))?;
+            continue;
+        };
+
+        fs::create_dir(&path).with_context(|| format!("Could not create {}", path.display()))?;
+        for &child in children {
+            let name = &file_system.objects[child].name;
+            // Anything else could write outside of `destination`
+            if !is_valid_name(name) {
+                bail!("Invalid name {name:?} in {}", file_system.path(object));
+            }
+            stack.push((child, path.join(name)));
+        }
+    }
+
+    Ok(())
+}
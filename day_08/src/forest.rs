@@ -0,0 +1,56 @@
+use anyhow::bail;
+
+// Parses one row of digit heights per line. Every row must have as many trees as the first; an
+// empty input is an empty forest.
+pub fn parse_forest(input: &str) -> anyhow::Result<Vec<Vec<u8>>> {
+    let mut tree_grid: Vec<Vec<u8>> = Vec::new();
+
+    for (row_index, line) in input.trim_end().lines().enumerate() {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(col_index, c)| match c.to_digit(10) {
+                Some(height) => Ok(height as u8),
+                None => bail!(
+                    "Row {}, column {}: expected a height from 0 to 9, got {c:?}",
+                    row_index + 1,
+                    col_index + 1
+                ),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if let Some(first_row) = tree_grid.first() {
+            if row.len() != first_row.len() {
+                bail!(
+                    "Row {} has {} trees, but row 1 has {}",
+                    row_index + 1,
+                    row.len(),
+                    first_row.len()
+                );
+            }
+        } else if row.is_empty() {
+            bail!("Row 1 is empty");
+        }
+
+        tree_grid.push(row);
+    }
+
+    Ok(tree_grid)
+}
+
+// A `rows` x `cols` forest of random heights from 0 to 9. The same seed always gives the same
+// forest.
+pub fn generate_forest(rows: usize, cols: usize, seed: u64) -> Vec<Vec<u8>> {
+    // xorshift64, which must not start from 0
+    let mut state = seed.max(1);
+    let mut next_height = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % 10) as u8
+    };
+
+    (0..rows)
+        .map(|_| (0..cols).map(|_| next_height()).collect())
+        .collect()
+}
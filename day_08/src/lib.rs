@@ -0,0 +1,4 @@
+pub mod forest;
+pub mod line_of_sight;
+pub mod scenic;
+pub mod visibility;
@@ -0,0 +1,71 @@
+// Cells crossed by the Bresenham line from `from` to `to`, both included
+pub fn bresenham_line(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut row, mut col) = (from.0 as isize, from.1 as isize);
+    let (to_row, to_col) = (to.0 as isize, to.1 as isize);
+
+    let row_distance = (to_row - row).abs();
+    let col_distance = -(to_col - col).abs();
+    let row_step = if row < to_row { 1 } else { -1 };
+    let col_step = if col < to_col { 1 } else { -1 };
+    let mut error = row_distance + col_distance;
+
+    let mut cells = Vec::new();
+    loop {
+        cells.push((row as usize, col as usize));
+        if (row, col) == (to_row, to_col) {
+            return cells;
+        }
+
+        let doubled_error = 2 * error;
+        if doubled_error >= col_distance {
+            error += col_distance;
+            row += row_step;
+        }
+        if doubled_error <= row_distance {
+            error += row_distance;
+            col += col_step;
+        }
+    }
+}
+
+// Someone standing in the forest, with their eyes at `eye_height` in the same unit as tree heights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    pub row: usize,
+    pub col: usize,
+    pub eye_height: f64,
+}
+
+impl Observer {
+    // Whether the top of the tree at `target` can be seen. The sight line goes along the Bresenham
+    // line and is blocked by any tree in between that reaches at least as high above it, going by
+    // the slope from the eyes to each tree top.
+    pub fn can_see(&self, tree_grid: &[Vec<u8>], target: (usize, usize)) -> bool {
+        let slope = |(row, col): (usize, usize)| {
+            let distance = (row as f64 - self.row as f64).hypot(col as f64 - self.col as f64);
+            (tree_grid[row][col] as f64 - self.eye_height) / distance
+        };
+
+        let line = bresenham_line((self.row, self.col), target);
+        let [_, between @ .., _] = &line[..] else {
+            // The observer's own tree
+            return true;
+        };
+
+        let target_slope = slope(target);
+        between.iter().all(|&cell| slope(cell) < target_slope)
+    }
+
+    // Which trees the observer can see, including the one they stand at
+    pub fn viewshed(&self, tree_grid: &[Vec<u8>]) -> Vec<Vec<bool>> {
+        tree_grid
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                (0..row.len())
+                    .map(|col_index| self.can_see(tree_grid, (row_index, col_index)))
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -1,99 +1,118 @@
-fn main() -> anyhow::Result<()> {
-    let input = include_str!("../input.txt");
-    let tree_grid: Vec<Vec<u8>> = input
-        .lines()
-        .map(|line| line.bytes().map(|b| b - b'0').collect())
-        .collect();
-
-    let mut visibility_grid: Vec<Vec<bool>> =
-        vec![vec![false; tree_grid[0].len()]; tree_grid.len()];
-
-    for row_index in 0..tree_grid.len() {
-        let row = &tree_grid[row_index];
-
-        // Mark trees visible from the left
-        let mut left_max_height = 0u8;
-        for col_index in 0..tree_grid[0].len() {
-            let height = row[col_index];
-
-            // All trees on the edge of the tree grid are visible
-            if col_index == 0 || col_index == tree_grid[0].len() - 1 {
-                visibility_grid[row_index][col_index] = true;
-            }
+use std::time::Instant;
 
-            // If the tree is taller than the tallest tree to the left, it is visible
-            if height > left_max_height {
-                visibility_grid[row_index][col_index] = true;
-                left_max_height = height;
-            }
-        }
+use day_08::{
+    forest::{generate_forest, parse_forest},
+    line_of_sight::Observer,
+    scenic::scenic_scores,
+    visibility::{Direction, VisibilityMap},
+};
 
-        // Mark trees visible from the right
-        let mut right_max_height = 0u8;
-        for col_index in (0..tree_grid[0].len()).rev() {
-            let height = row[col_index];
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    // Time the scenic scores of a generated square forest instead of solving the puzzle
+    if let Some(size) = option_value("--bench") {
+        let size = size.parse()?;
+        let tree_grid = generate_forest(size, size, 2022);
+
+        let start = Instant::now();
+        let scores = scenic_scores(&tree_grid);
+        let elapsed = start.elapsed();
+
+        let best = scores.iter().flatten().max().unwrap_or(&0);
+        println!("{size}x{size} forest: best scenic score {best} in {elapsed:?}");
+        return Ok(());
+    }
 
-            // If the tree is taller than the tallest tree to the right, it is visible
-            if height > right_max_height {
-                visibility_grid[row_index][col_index] = true;
-                right_max_height = height;
-            }
+    let input = match option_value("--input") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => include_str!("../input.txt").to_string(),
+    };
+    let tree_grid = parse_forest(&input)?;
+
+    // Lines of sight along the diagonals too, as a variant of part 1
+    let directions: &[Direction] = if flag("--diagonal") {
+        &Direction::ALL
+    } else {
+        &Direction::AXES
+    };
+    let visibility_map = VisibilityMap::new(&tree_grid, directions);
+
+    // Which sides a single tree is visible from, given as `row,col`
+    if let Some(position) = option_value("--tree") {
+        let (row_index, col_index) = parse_position(position, &tree_grid)?;
+
+        let sides = visibility_map.visible_from(row_index, col_index);
+        if sides.is_empty() {
+            println!("Tree at {position} is hidden");
+        } else {
+            let sides: Vec<String> = sides.iter().map(ToString::to_string).collect();
+            println!("Tree at {position} is visible from: {}", sides.join(", "));
         }
+        return Ok(());
     }
 
-    for col_index in 0..tree_grid[0].len() {
-        // Mark trees visible from the top
-        let mut top_max_height = 0u8;
-        for row_index in 0..tree_grid.len() {
-            let height = tree_grid[row_index][col_index];
-
-            // All trees on the edge of the tree grid are visible
-            if row_index == 0 || row_index == tree_grid.len() - 1 {
-                visibility_grid[row_index][col_index] = true;
-            }
-
-            // If the tree is taller than the tallest tree to the top, it is visible
-            if height > top_max_height {
-                visibility_grid[row_index][col_index] = true;
-                top_max_height = height;
+    // Map the trees an observer at `row,col` can see, with their eyes at the top of the tree there
+    // unless `--eye` says otherwise
+    if let Some(position) = option_value("--viewshed") {
+        let (row, col) = parse_position(position, &tree_grid)?;
+        let eye_height = match option_value("--eye") {
+            Some(eye_height) => eye_height.parse()?,
+            None => tree_grid[row][col] as f64,
+        };
+        let observer = Observer {
+            row,
+            col,
+            eye_height,
+        };
+
+        let viewshed = observer.viewshed(&tree_grid);
+        for (row_index, viewshed_row) in viewshed.iter().enumerate() {
+            for (col_index, &visible) in viewshed_row.iter().enumerate() {
+                let cell = match visible {
+                    _ if (row_index, col_index) == (row, col) => 'O',
+                    true => '#',
+                    false => '.',
+                };
+                print!("{cell}");
             }
+            println!();
         }
 
-        // Mark trees visible from the bottom
-        let mut bottom_max_height = 0u8;
-        for row_index in (0..tree_grid.len()).rev() {
-            let height = tree_grid[row_index][col_index];
-
-            // If the tree is taller than the tallest tree to the bottom, it is visible
-            if height > bottom_max_height {
-                visibility_grid[row_index][col_index] = true;
-                bottom_max_height = height;
-            }
-        }
+        let visible_count = viewshed
+            .iter()
+            .flatten()
+            .filter(|&&visible| visible)
+            .count();
+        println!("{visible_count} trees visible from {position} at height {eye_height}");
+        return Ok(());
     }
 
     // Print the forest :)
-    for row in &visibility_grid {
-        for &visible in row {
+    for (row_index, row) in tree_grid.iter().enumerate() {
+        for col_index in 0..row.len() {
+            let visible = visibility_map.is_visible(row_index, col_index);
             print!("{}", if visible { '#' } else { '.' });
         }
         println!();
     }
 
     // Count the number of visible trees
-    let part_1 = visibility_grid.iter().flatten().filter(|&&b| b).count();
+    let part_1 = visibility_map.visible_count();
 
     // Determine the maximum scenic score
-    let mut part_2 = 0;
-
-    for row_index in 0..tree_grid.len() {
-        for col_index in 0..tree_grid[0].len() {
-            let scenic_score = calculate_scenic_score(&tree_grid, row_index, col_index);
-            if scenic_score > part_2 {
-                part_2 = scenic_score;
-            }
-        }
-    }
+    let part_2 = scenic_scores(&tree_grid)
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0);
 
     println!("Part 1: {part_1}");
     println!("Part 2: {part_2}");
@@ -101,73 +120,15 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn calculate_scenic_score(tree_grid: &Vec<Vec<u8>>, row_index: usize, col_index: usize) -> usize {
-
-    // Trees on the edge of the grid always have at least one direction with no visible trees
-    // Hence, their scenic score is always 0.
-    if row_index == 0
-        || row_index == tree_grid.len() - 1
-        || col_index == 0
-        || col_index == tree_grid[0].len() - 1
-    {
-        return 0;
-    }
-
-    let height = tree_grid[row_index][col_index];
-
-    let mut scenic_score = 1;
-
-    // Count visible trees to the left
-    let mut visible_trees = 1;
-    let mut current_col = col_index - 1;
-    while tree_grid[row_index][current_col] < height {
-        if current_col == 0 {
-            break;
-        }
-        visible_trees += 1;
-        current_col -= 1;
-    }
-
-    scenic_score *= visible_trees;
-
-    // Count visible trees to the right
-    let mut visible_trees = 1;
-    let mut current_col = col_index + 1;
-    while tree_grid[row_index][current_col] < height {
-        if current_col == tree_grid[0].len() - 1 {
-            break;
-        }
-        visible_trees += 1;
-        current_col += 1;
-    }
-
-    scenic_score *= visible_trees;
+// A `row,col` position inside the grid
+fn parse_position(position: &str, tree_grid: &[Vec<u8>]) -> anyhow::Result<(usize, usize)> {
+    let Some((row_index, col_index)) = position.split_once(',') else {
+        anyhow::bail!("Expected a position like 3,4, got {position:?}");
+    };
+    let (row_index, col_index): (usize, usize) = (row_index.parse()?, col_index.parse()?);
 
-    // Count visible trees to the top
-    let mut visible_trees = 1;
-    let mut current_row = row_index - 1;
-    while tree_grid[current_row][col_index] < height {
-        if current_row == 0 {
-            break;
-        }
-        visible_trees += 1;
-        current_row -= 1;
+    match tree_grid.get(row_index).and_then(|row| row.get(col_index)) {
+        Some(_) => Ok((row_index, col_index)),
+        None => anyhow::bail!("No tree at {position}"),
     }
-
-    scenic_score *= visible_trees;
-
-    // Count visible trees to the bottom
-    let mut visible_trees = 1;
-    let mut current_row = row_index + 1;
-    while tree_grid[current_row][col_index] < height {
-        if current_row == tree_grid.len() - 1 {
-            break;
-        }
-        visible_trees += 1;
-        current_row += 1;
-    }
-
-    scenic_score *= visible_trees;
-
-    scenic_score
 }
@@ -0,0 +1,76 @@
+// Scenic score of every tree: the product of its viewing distances in the four directions, where a
+// viewing distance counts the trees up to and including the first one at least as tall, or up to
+// the edge. Trees on the edge have a score of 0.
+//
+// Each row and column is swept once per direction, so the whole grid takes O(rows * cols).
+pub fn scenic_scores(tree_grid: &[Vec<u8>]) -> Vec<Vec<usize>> {
+    let cols = tree_grid.first().map_or(0, Vec::len);
+    let mut scores = vec![vec![1; cols]; tree_grid.len()];
+
+    // Looking left, then right
+    let mut blockers = Blockers::default();
+    for (row, scores_row) in tree_grid.iter().zip(&mut scores) {
+        blockers.clear();
+        for (col_index, (&height, score)) in row.iter().zip(scores_row.iter_mut()).enumerate() {
+            *score *= blockers.viewing_distance(col_index, height);
+        }
+
+        blockers.clear();
+        let trees = row.iter().zip(scores_row.iter_mut()).rev();
+        for (col_index, (&height, score)) in trees.enumerate() {
+            *score *= blockers.viewing_distance(col_index, height);
+        }
+    }
+
+    // Looking up, then down. Columns are swept a row at a time, with blockers for each column, to
+    // go through memory in order.
+    let mut column_blockers = vec![Blockers::default(); cols];
+    for (row_index, (row, scores_row)) in tree_grid.iter().zip(&mut scores).enumerate() {
+        for ((&height, score), blockers) in row.iter().zip(scores_row).zip(&mut column_blockers) {
+            *score *= blockers.viewing_distance(row_index, height);
+        }
+    }
+
+    let mut column_blockers = vec![Blockers::default(); cols];
+    let rows = tree_grid.iter().zip(&mut scores).rev();
+    for (row_index, (row, scores_row)) in rows.enumerate() {
+        for ((&height, score), blockers) in row.iter().zip(scores_row).zip(&mut column_blockers) {
+            *score *= blockers.viewing_distance(row_index, height);
+        }
+    }
+
+    scores
+}
+
+// Trees along a line that could still block the view of the next ones, as (position, height) from
+// tallest to shortest. A tree hides every shorter tree behind it, so each tree is pushed and popped
+// at most once.
+#[derive(Debug, Clone, Default)]
+struct Blockers(Vec<(usize, u8)>);
+
+impl Blockers {
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    // Viewing distance towards the start of the line from the tree at `position`, which must come
+    // right after the previous one
+    fn viewing_distance(&mut self, position: usize, height: u8) -> usize {
+        while self
+            .0
+            .last()
+            .is_some_and(|&(_, blocker_height)| blocker_height < height)
+        {
+            self.0.pop();
+        }
+
+        // Up to the blocking tree, or to the edge
+        let viewing_distance = match self.0.last() {
+            Some(&(blocker_position, _)) => position - blocker_position,
+            None => position,
+        };
+
+        self.0.push((position, height));
+        viewing_distance
+    }
+}
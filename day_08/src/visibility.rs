@@ -0,0 +1,149 @@
+use std::fmt;
+
+// Side of the forest a tree is looked at from, including the corners for diagonal lines of sight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Direction {
+    pub const AXES: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Top,
+        Direction::Bottom,
+    ];
+    pub const ALL: [Direction; 8] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Top,
+        Direction::Bottom,
+        Direction::TopLeft,
+        Direction::TopRight,
+        Direction::BottomLeft,
+        Direction::BottomRight,
+    ];
+
+    // (row, col) step of a line of sight going into the forest from this side
+    fn step(self) -> (isize, isize) {
+        match self {
+            Direction::Left => (0, 1),
+            Direction::Right => (0, -1),
+            Direction::Top => (1, 0),
+            Direction::Bottom => (-1, 0),
+            Direction::TopLeft => (1, 1),
+            Direction::TopRight => (1, -1),
+            Direction::BottomLeft => (-1, 1),
+            Direction::BottomRight => (-1, -1),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Top => "top",
+            Direction::Bottom => "bottom",
+            Direction::TopLeft => "top-left",
+            Direction::TopRight => "top-right",
+            Direction::BottomLeft => "bottom-left",
+            Direction::BottomRight => "bottom-right",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// Which trees can be seen from outside the forest, one bitmap per direction. A tree is visible from
+// a side when every tree between it and that edge, along the line of sight, is shorter.
+#[derive(Debug, Clone)]
+pub struct VisibilityMap {
+    bitmaps: Vec<(Direction, Vec<Vec<bool>>)>,
+}
+
+impl VisibilityMap {
+    pub fn new(tree_grid: &[Vec<u8>], directions: &[Direction]) -> Self {
+        let bitmaps = directions
+            .iter()
+            .map(|&direction| (direction, sweep(tree_grid, direction)))
+            .collect();
+        Self { bitmaps }
+    }
+
+    pub fn bitmap(&self, direction: Direction) -> Option<&Vec<Vec<bool>>> {
+        self.bitmaps
+            .iter()
+            .find(|(bitmap_direction, _)| *bitmap_direction == direction)
+            .map(|(_, bitmap)| bitmap)
+    }
+
+    pub fn visible_from(&self, row_index: usize, col_index: usize) -> Vec<Direction> {
+        self.bitmaps
+            .iter()
+            .filter(|(_, bitmap)| bitmap[row_index][col_index])
+            .map(|&(direction, _)| direction)
+            .collect()
+    }
+
+    pub fn is_visible(&self, row_index: usize, col_index: usize) -> bool {
+        self.bitmaps
+            .iter()
+            .any(|(_, bitmap)| bitmap[row_index][col_index])
+    }
+
+    // Visible from at least one direction
+    pub fn visible_count(&self) -> usize {
+        let Some((_, first)) = self.bitmaps.first() else {
+            return 0;
+        };
+
+        (0..first.len())
+            .flat_map(|row_index| (0..first[row_index].len()).map(move |col| (row_index, col)))
+            .filter(|&(row_index, col_index)| self.is_visible(row_index, col_index))
+            .count()
+    }
+}
+
+// Follows every line of sight entering the forest from one side, from the edge inwards
+fn sweep(tree_grid: &[Vec<u8>], direction: Direction) -> Vec<Vec<bool>> {
+    let rows = tree_grid.len();
+    let cols = tree_grid.first().map_or(0, Vec::len);
+    let mut bitmap = vec![vec![false; cols]; rows];
+
+    let (row_step, col_step) = direction.step();
+    let offset = |row_index: usize, col_index: usize, row_step: isize, col_step: isize| {
+        let row_index = row_index.checked_add_signed(row_step)?;
+        let col_index = col_index.checked_add_signed(col_step)?;
+        (row_index < rows && col_index < cols).then_some((row_index, col_index))
+    };
+
+    // Lines of sight start at the trees with nothing in front of them, on the edge
+    for start_row in 0..rows {
+        for start_col in 0..cols {
+            if offset(start_row, start_col, -row_step, -col_step).is_some() {
+                continue;
+            }
+
+            let mut tallest: Option<u8> = None;
+            let mut current = Some((start_row, start_col));
+            while let Some((row_index, col_index)) = current {
+                let height = tree_grid[row_index][col_index];
+                if tallest.is_none_or(|tallest| height > tallest) {
+                    bitmap[row_index][col_index] = true;
+                    tallest = Some(height);
+                }
+                current = offset(row_index, col_index, row_step, col_step);
+            }
+        }
+    }
+
+    bitmap
+}
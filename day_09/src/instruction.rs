@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+
+use crate::rope::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction<const D: usize> {
+    // `R 4`, a diagonal like `UL 2`, or along any axis like `-z 3`
+    Move { direction: [i32; D], steps: u32 },
+    // `goto 3 -2`, with one coordinate per axis
+    Goto(Position<D>),
+}
+
+const AXES: [&str; 3] = ["x", "y", "z"];
+
+fn parse_direction<const D: usize>(name: &str) -> anyhow::Result<[i32; D]> {
+    // Along one axis, e.g. `+x` or `-z`
+    let axis_step = match name.split_at_checked(1) {
+        Some(("+", axis)) => Some((axis, 1)),
+        Some(("-", axis)) => Some((axis, -1)),
+        _ => None,
+    };
+    let steps = match axis_step {
+        Some((axis, step)) => match AXES.iter().position(|&name| name == axis) {
+            Some(axis_index) => vec![(axis_index, step)],
+            None => bail!("Unknown axis {axis:?}"),
+        },
+        // In the x-y plane
+        None => match name {
+            "R" => vec![(0, 1)],
+            "L" => vec![(0, -1)],
+            "U" => vec![(1, 1)],
+            "D" => vec![(1, -1)],
+            "UR" => vec![(0, 1), (1, 1)],
+            "UL" => vec![(0, -1), (1, 1)],
+            "DR" => vec![(0, 1), (1, -1)],
+            "DL" => vec![(0, -1), (1, -1)],
+            _ => bail!("Unknown direction {name:?}"),
+        },
+    };
+
+    let mut direction = [0; D];
+    for (axis_index, step) in steps {
+        match direction.get_mut(axis_index) {
+            Some(coordinate) => *coordinate = step,
+            None => bail!("Direction {name:?} needs more than {D} dimensions"),
+        }
+    }
+    Ok(direction)
+}
+
+impl<const D: usize> FromStr for Instruction<D> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+
+        if words.first() == Some(&"goto") {
+            if words.len() != D + 1 {
+                bail!("Expected `goto` followed by {D} coordinates");
+            }
+
+            let mut target = [0; D];
+            for (coordinate, value) in target.iter_mut().zip(&words[1..]) {
+                *coordinate = value
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid coordinate {value:?}"))?;
+            }
+            return Ok(Instruction::Goto(target));
+        }
+
+        let [direction, steps] = words[..] else {
+            bail!("Expected a direction and a number of steps, or `goto` and a position");
+        };
+
+        let direction = parse_direction(direction)?;
+        let steps = steps
+            .parse()
+            .map_err(|_| anyhow!("Invalid number of steps {steps:?}"))?;
+
+        Ok(Instruction::Move { direction, steps })
+    }
+}
+
+impl<const D: usize> Instruction<D> {
+    // Single steps taking the head from `head` through the instruction, one at a time. For `goto`,
+    // the head moves along every axis it is not yet in line with the target on.
+    pub fn head_steps(&self, head: Position<D>) -> impl Iterator<Item = [i32; D]> {
+        let instruction = *self;
+        let mut position = head;
+        let mut steps_taken = 0;
+
+        std::iter::from_fn(move || match instruction {
+            Instruction::Move { direction, steps } => (steps_taken < steps).then(|| {
+                steps_taken += 1;
+                direction
+            }),
+            Instruction::Goto(target) => (position != target).then(|| {
+                let mut step = [0; D];
+                for ((delta, coordinate), target) in
+                    step.iter_mut().zip(position.iter_mut()).zip(target)
+                {
+                    *delta = (target - *coordinate).signum();
+                    *coordinate += *delta;
+                }
+                step
+            }),
+        })
+    }
+}
+
+pub fn parse_instructions<const D: usize>(input: &str) -> anyhow::Result<Vec<Instruction<D>>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_index, line)| {
+            line.parse()
+                .map_err(|error| anyhow!("Line {}: {error}: {line:?}", line_index + 1))
+        })
+        .collect()
+}
@@ -1,53 +1,47 @@
-use std::collections::HashSet;
+use day_09::{instruction::parse_instructions, rope::Rope};
 
 fn main() -> anyhow::Result<()> {
-    let input = include_str!("../input.txt");
-
-    // Part 1
-    let mut positions_visited_part_1: HashSet<(i32, i32)> = HashSet::new();
-
-    let mut tail_position = (0, 0);
-    let mut head_position = (0, 0);
-
-    // Part 2
-    let mut positions_visited_part_2: HashSet<(i32, i32)> = HashSet::new();
-    let mut rope_positions: [(i32, i32); 10] = [(0, 0); 10];
-
-    for line in input.lines() {
-        let (direction, steps) = line.split_once(' ').unwrap();
-        let steps = steps.parse::<i32>()?;
-
-        let (dx, dy) = match direction {
-            "R" => (1, 0),
-            "L" => (-1, 0),
-            "U" => (0, 1),
-            "D" => (0, -1),
-            _ => panic!("Unknown direction"),
-        };
-
-        for _ in 0..steps {
-            // Part 1
-            head_position.0 += dx;
-            head_position.1 += dy;
-
-            move_tail(head_position, &mut tail_position);
-
-            positions_visited_part_1.insert(tail_position);
-
-            // Part 2
-            rope_positions[0].0 += dx;
-            rope_positions[0].1 += dy;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+
+    let input = match option_value("--input") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => include_str!("../input.txt").to_string(),
+    };
+
+    // Both parts come from the same 10-knot rope: its second knot moves like the tail of a 2-knot
+    // rope
+    let knot_count = match option_value("--knots") {
+        Some(knot_count) => knot_count.parse()?,
+        None => 10,
+    };
+
+    // The same rope in space, counting the voxels every knot visits
+    if flag("--3d") {
+        let rope: Rope<3> = simulate(&input, knot_count, flag("--trace"))?;
+        for knot in 0..knot_count {
+            println!("Knot {knot}: {} voxels", rope.visited(knot).unwrap().len());
+        }
+        return Ok(());
+    }
 
-            for i in 1..rope_positions.len() {
-                move_tail(rope_positions[i - 1], &mut rope_positions[i]);
-            }
+    let rope: Rope<2> = simulate(&input, knot_count, flag("--trace"))?;
 
-            positions_visited_part_2.insert(rope_positions[rope_positions.len() - 1]);
+    // Positions visited by every knot instead of the puzzle answers
+    if option_value("--knots").is_some() {
+        for knot in 0..knot_count {
+            println!("Knot {knot}: {}", rope.visited(knot).unwrap().len());
         }
+        return Ok(());
     }
 
-    let part_1 = positions_visited_part_1.len();
-    let part_2 = positions_visited_part_2.len();
+    let part_1 = rope.visited(1).unwrap().len();
+    let part_2 = rope.visited(9).unwrap().len();
 
     println!("Part 1: {part_1}");
     println!("Part 2: {part_2}");
@@ -55,43 +49,30 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn move_tail(head_position: (i32, i32), tail_position: &mut (i32, i32)) {
-    let tail_dx = head_position.0 - tail_position.0;
-    let tail_dy = head_position.1 - tail_position.1;
-    // up and right
-    if tail_dx > 1 && tail_dy > 0 || tail_dy > 1 && tail_dx > 0 {
-        tail_position.0 += 1;
-        tail_position.1 += 1;
-    }
-    // down and right
-    else if tail_dx > 1 && tail_dy < 0 || tail_dy < -1 && tail_dx > 0 {
-        tail_position.0 += 1;
-        tail_position.1 -= 1;
-    }
-    // down and left
-    else if tail_dx < -1 && tail_dy < 0 || tail_dy < -1 && tail_dx < 0 {
-        tail_position.0 -= 1;
-        tail_position.1 -= 1;
-    }
-    // up and left
-    else if tail_dx < -1 && tail_dy > 0 || tail_dy > 1 && tail_dx < 0 {
-        tail_position.0 -= 1;
-        tail_position.1 += 1;
-    }
-    // right
-    else if tail_dx > 1 {
-        tail_position.0 += 1;
-    }
-    // left
-    else if tail_dx < -1 {
-        tail_position.0 -= 1;
-    }
-    // up
-    else if tail_dy > 1 {
-        tail_position.1 += 1;
-    }
-    // down
-    else if tail_dy < -1 {
-        tail_position.1 -= 1;
+// Runs every instruction on a new rope, printing the knot positions after every step with `trace`
+fn simulate<const D: usize>(
+    input: &str,
+    knot_count: usize,
+    trace: bool,
+) -> anyhow::Result<Rope<D>> {
+    let mut rope = Rope::new(knot_count)?;
+
+    for instruction in parse_instructions::<D>(input)? {
+        for step in instruction.head_steps(rope.knots()[0]) {
+            let knots = rope.step(step);
+
+            if trace {
+                let knots: Vec<String> = knots
+                    .iter()
+                    .map(|knot| {
+                        let coordinates: Vec<String> = knot.iter().map(i32::to_string).collect();
+                        coordinates.join(",")
+                    })
+                    .collect();
+                println!("{}", knots.join(" "));
+            }
+        }
     }
+
+    Ok(rope)
 }
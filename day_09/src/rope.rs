@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use anyhow::bail;
+
+// Coordinates along each of the `D` axes: x, y, then z in 3D
+pub type Position<const D: usize> = [i32; D];
+
+// A rope of knots in `D` dimensions that all start at the origin. The head is moved one step at a
+// time and every other knot follows the one before it. Each knot remembers every position it has
+// been at.
+#[derive(Debug, Clone)]
+pub struct Rope<const D: usize> {
+    knots: Vec<Position<D>>,
+    visited: Vec<HashSet<Position<D>>>,
+}
+
+impl<const D: usize> Rope<D> {
+    pub fn new(knot_count: usize) -> anyhow::Result<Self> {
+        if knot_count == 0 {
+            bail!("A rope needs at least one knot");
+        }
+
+        Ok(Self {
+            knots: vec![[0; D]; knot_count],
+            visited: vec![HashSet::from([[0; D]]); knot_count],
+        })
+    }
+
+    // Positions of the knots, head first
+    pub fn knots(&self) -> &[Position<D>] {
+        &self.knots
+    }
+
+    // Moves the head by `step`, at most one along each axis, and lets the rest of the rope catch
+    // up, returning the new state
+    pub fn step(&mut self, step: [i32; D]) -> &[Position<D>] {
+        for (coordinate, delta) in self.knots[0].iter_mut().zip(step) {
+            *coordinate += delta;
+        }
+        self.visited[0].insert(self.knots[0]);
+
+        for i in 1..self.knots.len() {
+            let leader = self.knots[i - 1];
+            move_tail(leader, &mut self.knots[i]);
+            self.visited[i].insert(self.knots[i]);
+        }
+
+        &self.knots
+    }
+
+    // Positions knot `knot` has been at so far, the head being knot 0
+    pub fn visited(&self, knot: usize) -> Option<&HashSet<Position<D>>> {
+        self.visited.get(knot)
+    }
+}
+
+// Once the head is no longer touching the tail, even diagonally, the tail takes one step towards
+// it along every axis they differ on
+fn move_tail<const D: usize>(head_position: Position<D>, tail_position: &mut Position<D>) {
+    let touching = head_position
+        .iter()
+        .zip(tail_position.iter())
+        .all(|(head, tail)| (head - tail).abs() <= 1);
+
+    if !touching {
+        for (tail, head) in tail_position.iter_mut().zip(head_position) {
+            *tail += (head - *tail).signum();
+        }
+    }
+}